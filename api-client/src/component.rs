@@ -0,0 +1,100 @@
+use api_types::{
+    component::{Component, ComponentPrice, ComponentQuery, CreateComponentData, ImportReport, UpdateComponentData},
+    export::ExportFormat,
+    page::Page,
+};
+use reqwest::{header::CONTENT_TYPE, Method};
+use uuid::Uuid;
+
+use crate::{ClientResult, RigApiClient};
+
+impl RigApiClient {
+    /// `GET /components/<id>`, priced in `currency` if given
+    pub async fn get_component(&self, id: Uuid, currency: Option<&str>) -> ClientResult<Component> {
+        Self::json(
+            self.request(Method::GET, &format!("/components/{id}"))
+                .query(&[("currency", currency)])
+        ).await
+    }
+
+    /// `GET /components/<id>/prices`, oldest first
+    pub async fn list_component_prices(&self, id: Uuid) -> ClientResult<Vec<ComponentPrice>> {
+        Self::json(self.request(Method::GET, &format!("/components/{id}/prices"))).await
+    }
+
+    /// `GET /components`
+    pub async fn list_components(&self, query: &ComponentQuery) -> ClientResult<Page<Component>> {
+        Self::json(
+            self.request(Method::GET, "/components")
+                .query(query)
+        ).await
+    }
+
+    /// `GET /components/search`, best matches first
+    pub async fn search_components(&self, q: &str, limit: Option<i64>, currency: Option<&str>) -> ClientResult<Vec<Component>> {
+        Self::json(
+            self.request(Method::GET, "/components/search")
+                .query(&[("q", q)])
+                .query(&[("limit", limit)])
+                .query(&[("currency", currency)])
+        ).await
+    }
+
+    /// `GET /components/export`, the whole catalog as written by the backend
+    pub async fn export_components(&self, format: ExportFormat, currency: Option<&str>) -> ClientResult<String> {
+        Self::text(
+            self.request(Method::GET, "/components/export")
+                .query(&[("format", format.as_str())])
+                .query(&[("currency", currency)])
+        ).await
+    }
+
+    /// `POST /components`
+    pub async fn create_component(&self, component: &CreateComponentData) -> ClientResult<Component> {
+        Self::json(
+            self.request(Method::POST, "/components")
+                .json(component)
+        ).await
+    }
+
+    /// `POST /components/import` with a CSV catalog, in the columns
+    /// components are exported in
+    pub async fn import_components_csv(&self, csv: String, dry_run: bool) -> ClientResult<ImportReport> {
+        Self::json(
+            self.request(Method::POST, "/components/import")
+                .query(&[("dry_run", dry_run)])
+                .header(CONTENT_TYPE, "text/csv")
+                .body(csv)
+        ).await
+    }
+
+    /// `POST /components/import` with a JSON catalog
+    pub async fn import_components_json(&self, components: &[CreateComponentData], dry_run: bool) -> ClientResult<ImportReport> {
+        Self::json(
+            self.request(Method::POST, "/components/import")
+                .query(&[("dry_run", dry_run)])
+                .json(components)
+        ).await
+    }
+
+    /// `PUT /components/<id>`
+    pub async fn update_component(&self, id: Uuid, component: &CreateComponentData) -> ClientResult<Component> {
+        Self::json(
+            self.request(Method::PUT, &format!("/components/{id}"))
+                .json(component)
+        ).await
+    }
+
+    /// `PATCH /components/<id>`, changing only the fields that are set
+    pub async fn patch_component(&self, id: Uuid, changes: &UpdateComponentData) -> ClientResult<Component> {
+        Self::json(
+            self.request(Method::PATCH, &format!("/components/{id}"))
+                .json(changes)
+        ).await
+    }
+
+    /// `DELETE /components/<id>`
+    pub async fn delete_component(&self, id: Uuid) -> ClientResult<()> {
+        Self::empty(self.request(Method::DELETE, &format!("/components/{id}"))).await
+    }
+}
@@ -0,0 +1,24 @@
+use api_types::currency::{ExchangeRate, UpdateExchangeRateData};
+use reqwest::Method;
+
+use crate::{ClientResult, RigApiClient};
+
+impl RigApiClient {
+    /// `GET /exchange-rates`
+    pub async fn list_exchange_rates(&self) -> ClientResult<Vec<ExchangeRate>> {
+        Self::json(self.request(Method::GET, "/exchange-rates")).await
+    }
+
+    /// `GET /exchange-rates/<code>`
+    pub async fn get_exchange_rate(&self, code: &str) -> ClientResult<ExchangeRate> {
+        Self::json(self.request(Method::GET, &format!("/exchange-rates/{code}"))).await
+    }
+
+    /// `PUT /exchange-rates/<code>`
+    pub async fn update_exchange_rate(&self, code: &str, rate: &UpdateExchangeRateData) -> ClientResult<ExchangeRate> {
+        Self::json(
+            self.request(Method::PUT, &format!("/exchange-rates/{code}"))
+                .json(rate)
+        ).await
+    }
+}
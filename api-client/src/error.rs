@@ -0,0 +1,97 @@
+use std::fmt::Display;
+
+use api_types::error::ApiError;
+use reqwest::{Response, StatusCode};
+
+/// Why a call to the API failed
+#[derive(Debug)]
+pub enum ClientError {
+    /// The backend answered with one of its error bodies
+    Api {
+        status: StatusCode,
+        error: ApiError,
+    },
+    /// The backend answered with an error that isn't one of its bodies,
+    /// e.g. from a proxy in front of it
+    Unexpected {
+        status: StatusCode,
+        body: String,
+    },
+    /// The request couldn't be sent, didn't finish before the timeout or its
+    /// response couldn't be read
+    Http(reqwest::Error),
+}
+
+impl ClientError {
+    /// Reads the error out of a response whose status isn't a success
+    pub(crate) async fn from_response(response: Response) -> Self {
+        let status = response.status();
+        let body = match response.text().await {
+            Ok(body) => body,
+            Err(err) => return Self::Http(err),
+        };
+
+        match serde_json::from_str::<ApiError>(&body) {
+            Ok(error) => Self::Api { status, error },
+            Err(_) => Self::Unexpected { status, body },
+        }
+    }
+
+    /// Status the backend answered with, `None` if it never answered
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            Self::Api { status, .. } | Self::Unexpected { status, .. } => Some(*status),
+            Self::Http(err) => err.status(),
+        }
+    }
+
+    /// The backend's error body, with its `code`, `fields` and `details`
+    pub fn api_error(&self) -> Option<&ApiError> {
+        match self {
+            Self::Api { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+
+    pub fn is_not_found(&self) -> bool {
+        self.status() == Some(StatusCode::NOT_FOUND)
+    }
+
+    /// The token is missing, unknown or expired
+    pub fn is_unauthorized(&self) -> bool {
+        self.status() == Some(StatusCode::UNAUTHORIZED)
+    }
+
+    pub fn is_timeout(&self) -> bool {
+        matches!(self, Self::Http(err) if err.is_timeout())
+    }
+}
+
+impl Display for ClientError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Api { error, .. } => write!(f, "{error}"),
+            Self::Unexpected { status, body } if body.is_empty() => write!(f, "unexpected response: {status}"),
+            Self::Unexpected { status, body } => write!(f, "unexpected response: {status}: {body}"),
+            Self::Http(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Api { error, .. } => Some(error),
+            Self::Unexpected { .. } => None,
+            Self::Http(err) => Some(err),
+        }
+    }
+}
+
+impl From<reqwest::Error> for ClientError {
+    fn from(value: reqwest::Error) -> Self {
+        Self::Http(value)
+    }
+}
+
+pub type ClientResult<T> = Result<T, ClientError>;
@@ -0,0 +1,135 @@
+//! Async client for the rigs API, with a method for every route.
+//!
+//! Requests and responses are the [`api_types`] the backend is compiled
+//! against. It runs natively and on `wasm32`, where requests go through the
+//! browser's `fetch`.
+//!
+//! ```no_run
+//! # async fn run() -> api_client::ClientResult<()> {
+//! use std::time::Duration;
+//!
+//! use api_client::RigApiClient;
+//!
+//! let client = RigApiClient::new("http://localhost:8000")
+//!     .with_timeout(Duration::from_secs(10));
+//! let rigs = client.list_rigs(Some("BRL"), false).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::time::Duration;
+
+use reqwest::{Method, RequestBuilder, Response};
+use serde::de::DeserializeOwned;
+
+mod component;
+mod currency;
+mod error;
+mod revision;
+mod rig;
+mod slot;
+mod user;
+
+pub use error::{ClientError, ClientResult};
+
+#[derive(Debug, Clone)]
+pub struct RigApiClient {
+    http: reqwest::Client,
+    base_url: String,
+    timeout: Option<Duration>,
+    token: Option<String>,
+}
+
+impl RigApiClient {
+    /// A client for the API served at `base_url`, e.g. `http://localhost:8000`
+    pub fn new(base_url: impl Into<String>) -> Self {
+        let mut base_url = base_url.into();
+        base_url.truncate(base_url.trim_end_matches('/').len());
+
+        Self {
+            http: reqwest::Client::new(),
+            base_url,
+            timeout: None,
+            token: None,
+        }
+    }
+
+    /// Fails requests that take longer than `timeout`, from sending them to
+    /// reading their whole response
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        Self {
+            timeout: Some(timeout),
+            ..self
+        }
+    }
+
+    /// Authenticates requests with a session token, as returned by
+    /// [`login`](Self::login)
+    pub fn with_token(self, token: impl Into<String>) -> Self {
+        Self {
+            token: Some(token.into()),
+            ..self
+        }
+    }
+
+    /// Sends requests through `http`, e.g. to share its connection pool or
+    /// set up proxies
+    pub fn with_http_client(self, http: reqwest::Client) -> Self {
+        Self {
+            http,
+            ..self
+        }
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    pub fn token(&self) -> Option<&str> {
+        self.token.as_deref()
+    }
+
+    pub fn set_token(&mut self, token: Option<String>) {
+        self.token = token;
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        let mut request = self.http.request(method, format!("{}{path}", self.base_url));
+
+        if let Some(timeout) = self.timeout {
+            request = request.timeout(timeout);
+        }
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+
+        request
+    }
+
+    async fn send(request: RequestBuilder) -> ClientResult<Response> {
+        let response = request.send().await?;
+
+        if response.status().is_success() {
+            Ok(response)
+        } else {
+            Err(ClientError::from_response(response).await)
+        }
+    }
+
+    async fn json<T: DeserializeOwned>(request: RequestBuilder) -> ClientResult<T> {
+        Ok(Self::send(request).await?.json().await?)
+    }
+
+    async fn text(request: RequestBuilder) -> ClientResult<String> {
+        Ok(Self::send(request).await?.text().await?)
+    }
+
+    async fn empty(request: RequestBuilder) -> ClientResult<()> {
+        Self::send(request).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,39 @@
+use api_types::{revision::{RestoreRevisionQuery, RevisionDiff, RigRevisionWithComponents}, rig::RigWithComponents};
+use reqwest::Method;
+use uuid::Uuid;
+
+use crate::{ClientResult, RigApiClient};
+
+impl RigApiClient {
+    /// `GET /rigs/<id>/revisions`, oldest first
+    pub async fn list_rig_revisions(&self, id: Uuid, currency: Option<&str>) -> ClientResult<Vec<RigRevisionWithComponents>> {
+        Self::json(
+            self.request(Method::GET, &format!("/rigs/{id}/revisions"))
+                .query(&[("currency", currency)])
+        ).await
+    }
+
+    /// `GET /rigs/<id>/revisions/<number>`
+    pub async fn get_rig_revision(&self, id: Uuid, number: i32, currency: Option<&str>) -> ClientResult<RigRevisionWithComponents> {
+        Self::json(
+            self.request(Method::GET, &format!("/rigs/{id}/revisions/{number}"))
+                .query(&[("currency", currency)])
+        ).await
+    }
+
+    /// `GET /rigs/<id>/revisions/<from>/diff/<to>`
+    pub async fn diff_rig_revisions(&self, id: Uuid, from: i32, to: i32, currency: Option<&str>) -> ClientResult<RevisionDiff> {
+        Self::json(
+            self.request(Method::GET, &format!("/rigs/{id}/revisions/{from}/diff/{to}"))
+                .query(&[("currency", currency)])
+        ).await
+    }
+
+    /// `POST /rigs/<id>/revisions/<number>/restore`
+    pub async fn restore_rig_revision(&self, id: Uuid, number: i32, query: &RestoreRevisionQuery) -> ClientResult<RigWithComponents> {
+        Self::json(
+            self.request(Method::POST, &format!("/rigs/{id}/revisions/{number}/restore"))
+                .query(query)
+        ).await
+    }
+}
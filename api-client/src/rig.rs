@@ -0,0 +1,113 @@
+use api_types::{
+    compatibility::CompatibilityReport,
+    export::ExportFormat,
+    rig::{CloneRigData, CreateRigData, RenameRigData, Rig, RigValuation, RigWithComponents, ShareRigData, UpdateRigComponentsData},
+};
+use reqwest::Method;
+use time::Date;
+use uuid::Uuid;
+
+use crate::{ClientResult, RigApiClient};
+
+impl RigApiClient {
+    /// `GET /rigs`, only the client's user's rigs when `mine` is set
+    pub async fn list_rigs(&self, currency: Option<&str>, mine: bool) -> ClientResult<Vec<RigWithComponents>> {
+        Self::json(
+            self.request(Method::GET, "/rigs")
+                .query(&[("currency", currency)])
+                .query(&[("mine", mine.then_some(true))])
+        ).await
+    }
+
+    /// `GET /rigs/export`, only the client's user's rigs when `mine` is set
+    pub async fn export_rigs(&self, format: ExportFormat, currency: Option<&str>, mine: bool) -> ClientResult<String> {
+        Self::text(
+            self.request(Method::GET, "/rigs/export")
+                .query(&[("format", format.as_str())])
+                .query(&[("currency", currency)])
+                .query(&[("mine", mine.then_some(true))])
+        ).await
+    }
+
+    /// `GET /rigs/<id>`
+    pub async fn get_rig(&self, id: Uuid, currency: Option<&str>) -> ClientResult<RigWithComponents> {
+        Self::json(
+            self.request(Method::GET, &format!("/rigs/{id}"))
+                .query(&[("currency", currency)])
+        ).await
+    }
+
+    /// `GET /rigs/<id>/valuation`, at the end of `at` or today
+    pub async fn get_rig_valuation(&self, id: Uuid, at: Option<Date>, currency: Option<&str>) -> ClientResult<RigValuation> {
+        Self::json(
+            self.request(Method::GET, &format!("/rigs/{id}/valuation"))
+                .query(&[("at", at.map(|at| at.to_string()))])
+                .query(&[("currency", currency)])
+        ).await
+    }
+
+    /// `POST /rigs`
+    pub async fn create_rig(&self, rig: &CreateRigData, currency: Option<&str>) -> ClientResult<RigWithComponents> {
+        Self::json(
+            self.request(Method::POST, "/rigs")
+                .query(&[("currency", currency)])
+                .json(rig)
+        ).await
+    }
+
+    /// `POST /rigs/compatibility`
+    pub async fn check_rig_compatibility(&self, rig: &CreateRigData) -> ClientResult<CompatibilityReport> {
+        Self::json(
+            self.request(Method::POST, "/rigs/compatibility")
+                .json(rig)
+        ).await
+    }
+
+    /// `PATCH /rigs/<id>`
+    pub async fn rename_rig(&self, id: Uuid, rig: &RenameRigData) -> ClientResult<Rig> {
+        Self::json(
+            self.request(Method::PATCH, &format!("/rigs/{id}"))
+                .json(rig)
+        ).await
+    }
+
+    /// `PUT /rigs/<id>/components`
+    pub async fn update_rig_components(&self, id: Uuid, components: &UpdateRigComponentsData, currency: Option<&str>) -> ClientResult<RigWithComponents> {
+        Self::json(
+            self.request(Method::PUT, &format!("/rigs/{id}/components"))
+                .query(&[("currency", currency)])
+                .json(components)
+        ).await
+    }
+
+    /// `POST /rigs/<id>/clone`
+    pub async fn clone_rig(&self, id: Uuid, rig: &CloneRigData, currency: Option<&str>) -> ClientResult<RigWithComponents> {
+        Self::json(
+            self.request(Method::POST, &format!("/rigs/{id}/clone"))
+                .query(&[("currency", currency)])
+                .json(rig)
+        ).await
+    }
+
+    /// `POST /rigs/<id>/share`
+    pub async fn share_rig(&self, id: Uuid, share: &ShareRigData) -> ClientResult<Rig> {
+        Self::json(
+            self.request(Method::POST, &format!("/rigs/{id}/share"))
+                .json(share)
+        ).await
+    }
+
+    /// `GET /shared/<slug>`, answered with a not found error once the link
+    /// is revoked
+    pub async fn get_shared_rig(&self, slug: &str, currency: Option<&str>) -> ClientResult<RigWithComponents> {
+        Self::json(
+            self.request(Method::GET, &format!("/shared/{slug}"))
+                .query(&[("currency", currency)])
+        ).await
+    }
+
+    /// `DELETE /rigs/<id>`
+    pub async fn delete_rig(&self, id: Uuid) -> ClientResult<()> {
+        Self::empty(self.request(Method::DELETE, &format!("/rigs/{id}"))).await
+    }
+}
@@ -0,0 +1,37 @@
+use api_types::slot::{CreateSlotData, Slot, UpdateSlotData};
+use reqwest::Method;
+
+use crate::{ClientResult, RigApiClient};
+
+impl RigApiClient {
+    /// `GET /slots`, in display order
+    pub async fn list_slots(&self) -> ClientResult<Vec<Slot>> {
+        Self::json(self.request(Method::GET, "/slots")).await
+    }
+
+    /// `GET /slots/<key>`
+    pub async fn get_slot(&self, key: &str) -> ClientResult<Slot> {
+        Self::json(self.request(Method::GET, &format!("/slots/{key}"))).await
+    }
+
+    /// `POST /slots`
+    pub async fn create_slot(&self, slot: &CreateSlotData) -> ClientResult<Slot> {
+        Self::json(
+            self.request(Method::POST, "/slots")
+                .json(slot)
+        ).await
+    }
+
+    /// `PUT /slots/<key>`
+    pub async fn update_slot(&self, key: &str, slot: &UpdateSlotData) -> ClientResult<Slot> {
+        Self::json(
+            self.request(Method::PUT, &format!("/slots/{key}"))
+                .json(slot)
+        ).await
+    }
+
+    /// `DELETE /slots/<key>`
+    pub async fn delete_slot(&self, key: &str) -> ClientResult<()> {
+        Self::empty(self.request(Method::DELETE, &format!("/slots/{key}"))).await
+    }
+}
@@ -0,0 +1,47 @@
+use api_types::user::{CredentialsData, SessionData, UpdateRoleData, User};
+use reqwest::Method;
+use uuid::Uuid;
+
+use crate::{ClientResult, RigApiClient};
+
+impl RigApiClient {
+    /// `POST /auth/register`
+    pub async fn register(&self, credentials: &CredentialsData) -> ClientResult<User> {
+        Self::json(
+            self.request(Method::POST, "/auth/register")
+                .json(credentials)
+        ).await
+    }
+
+    /// `POST /auth/login`. The client isn't changed; pass the session's token
+    /// to [`with_token`](Self::with_token) to act as the user.
+    pub async fn login(&self, credentials: &CredentialsData) -> ClientResult<SessionData> {
+        Self::json(
+            self.request(Method::POST, "/auth/login")
+                .json(credentials)
+        ).await
+    }
+
+    /// `POST /auth/logout`, ending the session of the client's token
+    pub async fn logout(&self) -> ClientResult<()> {
+        Self::empty(self.request(Method::POST, "/auth/logout")).await
+    }
+
+    /// `GET /auth/me`
+    pub async fn get_current_user(&self) -> ClientResult<User> {
+        Self::json(self.request(Method::GET, "/auth/me")).await
+    }
+
+    /// `GET /users`
+    pub async fn list_users(&self) -> ClientResult<Vec<User>> {
+        Self::json(self.request(Method::GET, "/users")).await
+    }
+
+    /// `PUT /users/<id>/role`
+    pub async fn update_user_role(&self, id: Uuid, role: &UpdateRoleData) -> ClientResult<User> {
+        Self::json(
+            self.request(Method::PUT, &format!("/users/{id}/role"))
+                .json(role)
+        ).await
+    }
+}
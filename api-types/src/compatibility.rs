@@ -0,0 +1,18 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// A single reason why two or more components can't be part of the same rig.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Incompatibility {
+    pub rule: String,
+    pub components: Vec<Uuid>,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CompatibilityReport {
+    pub compatible: bool,
+    pub incompatibilities: Vec<Incompatibility>,
+}
@@ -0,0 +1,205 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::{error::FieldError, money::Money, page::PageQuery, types::{Interface, MemoryGeneration}};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(
+    feature = "diesel",
+    derive(diesel::Queryable, diesel::Insertable, diesel::Identifiable, diesel::Selectable),
+    diesel(table_name = crate::schema::components),
+)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Component {
+    pub id: Uuid,
+    pub manufacturer: String,
+    pub model: String,
+    /// Key of the [`Slot`](crate::slot::Slot) the component fits in
+    pub slot: String,
+    #[cfg_attr(feature = "diesel", diesel(embed))]
+    pub price: Money,
+    pub socket: Option<String>,
+    pub memory_generations: Vec<MemoryGeneration>,
+    pub interfaces: Vec<Interface>,
+}
+
+impl Component {
+    pub fn new(manufacturer: String, model: String, slot: String, price: Money) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            manufacturer,
+            model,
+            slot,
+            price,
+            socket: None,
+            memory_generations: Vec::new(),
+            interfaces: Vec::new(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CreateComponentData {
+    pub manufacturer: String,
+    pub model: String,
+    pub slot: String,
+    pub price: Money,
+    #[serde(default)]
+    pub socket: Option<String>,
+    #[serde(default)]
+    pub memory_generations: Vec<MemoryGeneration>,
+    #[serde(default)]
+    pub interfaces: Vec<Interface>,
+}
+
+impl From<CreateComponentData> for Component {
+    fn from(value: CreateComponentData) -> Self {
+        let CreateComponentData { manufacturer, model, slot, price, socket, memory_generations, interfaces } = value;
+        Self {
+            socket,
+            memory_generations,
+            interfaces,
+            ..Self::new(manufacturer, model, slot, price)
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct UpdateComponentData {
+    pub manufacturer: Option<String>,
+    pub model: Option<String>,
+    pub slot: Option<String>,
+    pub price: Option<Money>,
+    pub socket: Option<String>,
+    pub memory_generations: Option<Vec<MemoryGeneration>>,
+    pub interfaces: Option<Vec<Interface>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[cfg_attr(feature = "rocket", derive(rocket::FromFormField))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum ComponentSort {
+    #[serde(rename = "price")]
+    #[cfg_attr(feature = "rocket", field(value = "price"))]
+    PriceAsc,
+    #[serde(rename = "-price")]
+    #[cfg_attr(feature = "rocket", field(value = "-price"))]
+    PriceDesc,
+    #[serde(rename = "model")]
+    #[cfg_attr(feature = "rocket", field(value = "model"))]
+    Model,
+}
+
+/// Filters, order and page of `GET /components`
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[cfg_attr(feature = "rocket", derive(rocket::FromForm))]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams), into_params(parameter_in = Query))]
+pub struct ComponentQuery {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slot: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub manufacturer: Option<String>,
+    /// In cents of `currency`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_price: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_price: Option<i64>,
+    /// Currency `min_price`/`max_price` are in and prices get converted to.
+    /// Without it bounds are in the base currency and prices are left as stored.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub currency: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort: Option<ComponentSort>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub per_page: Option<i64>,
+}
+
+impl ComponentQuery {
+    pub fn pagination(&self) -> PageQuery {
+        PageQuery::new(self.page, self.per_page)
+    }
+}
+
+/// A price a [`Component`] had from `changed_at` until its next entry
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(
+    feature = "diesel",
+    derive(diesel::Queryable, diesel::Identifiable, diesel::Associations),
+    diesel(belongs_to(Component)),
+    diesel(table_name = crate::schema::component_prices),
+)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ComponentPrice {
+    pub id: i64,
+    pub component_id: Uuid,
+    pub price: Money,
+    #[serde(with = "time::serde::rfc3339")]
+    pub changed_at: OffsetDateTime,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum ImportStatus {
+    Created,
+    Updated,
+    Rejected,
+}
+
+/// What happened, or would happen on a dry run, to one row of an import.
+/// Rows are numbered from 1, not counting the CSV header.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ImportRowReport {
+    pub row: usize,
+    pub status: ImportStatus,
+    /// The created or updated component; `None` for rejected rows and rows
+    /// a dry run would create
+    pub id: Option<Uuid>,
+    pub manufacturer: Option<String>,
+    pub model: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<FieldError>,
+}
+
+impl ImportRowReport {
+    pub fn rejected(row: usize, component: Option<&CreateComponentData>, errors: Vec<FieldError>) -> Self {
+        Self {
+            row,
+            status: ImportStatus::Rejected,
+            id: None,
+            manufacturer: component.map(|component| component.manufacturer.clone()),
+            model: component.map(|component| component.model.clone()),
+            errors,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ImportReport {
+    pub dry_run: bool,
+    pub created: usize,
+    pub updated: usize,
+    pub rejected: usize,
+    pub rows: Vec<ImportRowReport>,
+}
+
+impl ImportReport {
+    pub fn new(dry_run: bool, rows: Vec<ImportRowReport>) -> Self {
+        let count = |status| rows.iter().filter(|row| row.status == status).count();
+
+        Self {
+            dry_run,
+            created: count(ImportStatus::Created),
+            updated: count(ImportStatus::Updated),
+            rejected: count(ImportStatus::Rejected),
+            rows,
+        }
+    }
+}
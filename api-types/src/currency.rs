@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(
+    feature = "diesel",
+    derive(diesel::Queryable, diesel::Identifiable, diesel::Selectable),
+    diesel(table_name = crate::schema::exchange_rates),
+    diesel(primary_key(currency)),
+)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ExchangeRate {
+    /// ISO 4217 code
+    pub currency: String,
+    /// Value of one unit of the currency in the base currency, in millionths
+    pub rate_micros: i64,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct UpdateExchangeRateData {
+    pub rate_micros: i64,
+}
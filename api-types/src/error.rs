@@ -0,0 +1,41 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+/// Body of every error the API answers with. `code` is meant for programs,
+/// `message` for people; `fields` is only filled for validation failures and
+/// `details` carries whatever else the error needs to be acted upon.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ApiError {
+    pub code: String,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<FieldError>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<Value>,
+}
+
+impl Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.fields.is_empty() {
+            return write!(f, "{}", self.message);
+        }
+
+        let messages: Vec<String> = self.fields
+            .iter()
+            .map(|error| format!("{}: {}", error.field, error.message))
+            .collect();
+
+        write!(f, "{}", messages.join("\n"))
+    }
+}
+
+impl std::error::Error for ApiError {}
@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+
+/// What `GET /components/export` and `GET /rigs/export` are written as
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "rocket", derive(rocket::FromFormField))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema), schema(rename_all = "lowercase"))]
+pub enum ExportFormat {
+    #[cfg_attr(feature = "rocket", field(value = "csv"))]
+    Csv,
+    #[cfg_attr(feature = "rocket", field(value = "json"))]
+    Json,
+    #[cfg_attr(feature = "rocket", field(value = "ndjson"))]
+    Ndjson,
+}
+
+impl ExportFormat {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Json => "json",
+            Self::Ndjson => "ndjson",
+        }
+    }
+
+    pub fn media_type(self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv",
+            Self::Json => "application/json",
+            Self::Ndjson => "application/x-ndjson",
+        }
+    }
+}
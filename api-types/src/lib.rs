@@ -0,0 +1,22 @@
+//! Types exchanged by the backend and its clients, so both sides of the API
+//! are compiled against the same definitions.
+//!
+//! Everything here serializes as it goes over the wire. The features add what
+//! only one side needs: `diesel` the table definitions and row derives,
+//! `rocket` the query string derives, `openapi` the schemas and `leptos` the
+//! views.
+
+pub mod compatibility;
+pub mod component;
+pub mod currency;
+pub mod error;
+pub mod export;
+pub mod money;
+pub mod page;
+pub mod revision;
+pub mod rig;
+#[cfg(feature = "diesel")]
+pub mod schema;
+pub mod slot;
+pub mod types;
+pub mod user;
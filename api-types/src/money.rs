@@ -0,0 +1,100 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+/// An amount of money in the minor unit (cents) of an ISO 4217 currency
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "diesel", derive(diesel::Queryable, diesel::Insertable, diesel::Selectable), diesel(table_name = crate::schema::components))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Money {
+    #[cfg_attr(feature = "diesel", diesel(column_name = price_cents))]
+    pub amount: i64,
+    pub currency: String,
+}
+
+impl Money {
+    pub fn new(amount: i64, currency: impl Into<String>) -> Self {
+        Self {
+            amount,
+            currency: currency.into(),
+        }
+    }
+
+    pub fn zero(currency: impl Into<String>) -> Self {
+        Self::new(0, currency)
+    }
+
+    /// `None` if the amount is too large to hold
+    pub fn times(&self, quantity: i32) -> Option<Self> {
+        let amount = self.amount.checked_mul(i64::from(quantity))?;
+
+        Some(Self::new(amount, self.currency.clone()))
+    }
+
+    /// [`Money::times`], saturating instead for amounts too large to hold.
+    /// Meant for showing prices that were validated, but may have grown
+    /// since by being converted.
+    pub fn saturating_times(&self, quantity: i32) -> Self {
+        Self::new(self.amount.saturating_mul(i64::from(quantity)), self.currency.clone())
+    }
+
+    /// Sums amounts that are all in `currency`, `None` if the total is too
+    /// large to hold
+    pub fn sum<'a>(currency: &str, amounts: impl IntoIterator<Item = &'a Money>) -> Option<Self> {
+        let amount = amounts
+            .into_iter()
+            .inspect(|money| debug_assert_eq!(money.currency, currency))
+            .try_fold(0i64, |total, money| total.checked_add(money.amount))?;
+
+        Some(Self::new(amount, currency))
+    }
+
+    /// [`Money::sum`], saturating instead for totals too large to hold
+    pub fn saturating_sum<'a>(currency: &str, amounts: impl IntoIterator<Item = &'a Money>) -> Self {
+        let amount = amounts
+            .into_iter()
+            .inspect(|money| debug_assert_eq!(money.currency, currency))
+            .fold(0i64, |total, money| total.saturating_add(money.amount));
+
+        Self::new(amount, currency)
+    }
+
+    fn symbol(&self) -> &str {
+        match self.currency.as_str() {
+            "BRL" => "R$",
+            "USD" => "US$",
+            "EUR" => "€",
+            currency => currency,
+        }
+    }
+
+    fn decimal_separator(&self) -> char {
+        match self.currency.as_str() {
+            "USD" => '.',
+            _ => ',',
+        }
+    }
+}
+
+impl Display for Money {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let sign = if self.amount < 0 { "-" } else { "" };
+        let amount = self.amount.unsigned_abs();
+
+        write!(
+            f,
+            "{sign}{} {}{}{:02}",
+            self.symbol(),
+            amount / 100,
+            self.decimal_separator(),
+            amount % 100,
+        )
+    }
+}
+
+#[cfg(feature = "leptos")]
+impl leptos::IntoView for Money {
+    fn into_view(self) -> leptos::View {
+        self.to_string().into_view()
+    }
+}
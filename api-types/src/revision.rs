@@ -0,0 +1,169 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::money::Money;
+
+/// A component as it was when a revision was taken, priced at that time
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "diesel", derive(diesel::Queryable))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RevisionComponent {
+    pub component_id: Uuid,
+    pub manufacturer: String,
+    pub model: String,
+    pub slot: String,
+    pub quantity: i32,
+    pub price: Money,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RevisionItem {
+    #[serde(flatten)]
+    pub component: RevisionComponent,
+    pub line_total: Money,
+}
+
+impl RevisionItem {
+    pub fn new(component: RevisionComponent) -> Self {
+        let line_total = component.price.saturating_times(component.quantity);
+        Self {
+            component,
+            line_total,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RigRevisionWithComponents {
+    pub number: i32,
+    pub name: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    pub components: Vec<RevisionItem>,
+    pub total_price: Money,
+}
+
+/// A component replaced by another one, or whose quantity changed
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ComponentChange {
+    pub from: RevisionItem,
+    pub to: RevisionItem,
+}
+
+/// How a slot's components differ between two revisions. Removed and added
+/// components are paired up as swaps, in the order they were stored; the
+/// ones left over stay removed or added.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SlotDiff {
+    pub slot: String,
+    pub added: Vec<RevisionItem>,
+    pub removed: Vec<RevisionItem>,
+    pub swapped: Vec<ComponentChange>,
+    pub quantity_changed: Vec<ComponentChange>,
+}
+
+/// The diff of `slot`, created on its first change
+fn slot_diff<'a>(slots: &'a mut BTreeMap<String, SlotDiff>, slot: &str) -> &'a mut SlotDiff {
+    slots.entry(slot.to_owned()).or_insert_with(|| SlotDiff {
+        slot: slot.to_owned(),
+        ..SlotDiff::default()
+    })
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RevisionDiff {
+    pub from: i32,
+    pub to: i32,
+    pub from_name: String,
+    pub to_name: String,
+    /// Only the slots that changed, by slot key
+    pub slots: Vec<SlotDiff>,
+    pub from_total: Money,
+    pub to_total: Money,
+    /// `to_total` minus `from_total`
+    pub price_delta: Money,
+}
+
+impl RevisionDiff {
+    /// Both revisions must be priced in `currency`
+    pub fn new(from: RigRevisionWithComponents, to: RigRevisionWithComponents, currency: &str) -> Self {
+        let mut slots: BTreeMap<String, SlotDiff> = BTreeMap::new();
+
+        let mut remaining: HashMap<Uuid, RevisionItem> = to.components
+            .iter()
+            .map(|item| (item.component.component_id, item.clone()))
+            .collect();
+        let mut removed: BTreeMap<String, Vec<RevisionItem>> = BTreeMap::new();
+
+        for item in from.components {
+            match remaining.remove(&item.component.component_id) {
+                Some(other) if other.component.quantity != item.component.quantity => {
+                    slot_diff(&mut slots, &item.component.slot).quantity_changed.push(ComponentChange { from: item, to: other });
+                },
+                Some(_) => {},
+                None => removed.entry(item.component.slot.clone()).or_default().push(item),
+            }
+        }
+
+        let mut added: BTreeMap<String, Vec<RevisionItem>> = BTreeMap::new();
+        // Keeps the order components were stored in
+        for item in to.components {
+            if remaining.contains_key(&item.component.component_id) {
+                added.entry(item.component.slot.clone()).or_default().push(item);
+            }
+        }
+
+        for (slot, removed) in removed {
+            let mut added = added.remove(&slot).unwrap_or_default().into_iter();
+            let diff = slot_diff(&mut slots, &slot);
+
+            for item in removed {
+                match added.next() {
+                    Some(other) => diff.swapped.push(ComponentChange { from: item, to: other }),
+                    None => diff.removed.push(item),
+                }
+            }
+
+            diff.added.extend(added);
+        }
+
+        for (slot, added) in added {
+            slot_diff(&mut slots, &slot).added.extend(added);
+        }
+
+        let price_delta = Money::new(to.total_price.amount - from.total_price.amount, currency);
+
+        Self {
+            from: from.number,
+            to: to.number,
+            from_name: from.name,
+            to_name: to.name,
+            slots: slots.into_values().collect(),
+            from_total: from.total_price,
+            to_total: to.total_price,
+            price_delta,
+        }
+    }
+}
+
+/// Options of `POST /rigs/<id>/revisions/<number>/restore`
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[cfg_attr(feature = "rocket", derive(rocket::FromForm))]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams), into_params(parameter_in = Query))]
+pub struct RestoreRevisionQuery {
+    /// Currency to convert prices to, BRL without it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub currency: Option<String>,
+    /// Restore the components even if they aren't compatible with each other
+    #[serde(default)]
+    #[cfg_attr(feature = "rocket", field(default = false))]
+    pub allow_incompatible: bool,
+}
@@ -0,0 +1,221 @@
+use serde::{Deserialize, Serialize};
+use time::Date;
+use uuid::Uuid;
+
+use crate::{component::Component, money::Money};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(
+    feature = "diesel",
+    derive(diesel::Queryable, diesel::Insertable, diesel::Identifiable, diesel::Selectable, diesel::AsChangeset),
+    diesel(table_name = crate::schema::rigs),
+)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Rig {
+    pub id: Uuid,
+    pub name: String,
+    /// `None` for rigs created before accounts existed
+    pub owner_id: Option<Uuid>,
+    /// Set while the rig can be read by anyone at `/shared/<share_slug>`
+    pub share_slug: Option<String>,
+    /// Rig this one was cloned from, `None` if it was built from scratch or
+    /// the original is gone
+    pub parent_rig_id: Option<Uuid>,
+}
+
+impl Rig {
+    pub fn new(name: String, owner_id: Uuid) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            name,
+            owner_id: Some(owner_id),
+            share_slug: None,
+            parent_rig_id: None,
+        }
+    }
+
+    /// A new, unshared rig owned by `owner_id` that remembers it came from
+    /// this one
+    pub fn fork(&self, name: String, owner_id: Uuid) -> Self {
+        Self {
+            parent_rig_id: Some(self.id),
+            ..Self::new(name, owner_id)
+        }
+    }
+
+    /// `components` must already be priced in `currency`
+    pub fn with_components(self, components: Vec<RigComponentItem>, currency: &str) -> RigWithComponents {
+        let Self { id, name, owner_id, share_slug, parent_rig_id } = self;
+        let total_price = Money::saturating_sum(currency, components.iter().map(|item| &item.line_total));
+
+        RigWithComponents {
+            id,
+            name,
+            owner_id,
+            share_slug,
+            parent_rig_id,
+            components,
+            total_price,
+        }
+    }
+}
+
+fn default_quantity() -> i32 {
+    1
+}
+
+/// A component to put in a rig, and how many of it
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RigComponentData {
+    pub component_id: Uuid,
+    /// From 1 to 1000
+    #[serde(default = "default_quantity")]
+    pub quantity: i32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CreateRigData {
+    pub name: String,
+    pub components: Vec<RigComponentData>,
+    /// Creates the rig even if its components fail the compatibility rules
+    #[serde(default)]
+    pub allow_incompatible: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RigComponentItem {
+    #[serde(flatten)]
+    pub component: Component,
+    pub quantity: i32,
+    pub line_total: Money,
+}
+
+impl RigComponentItem {
+    pub fn new(component: Component, quantity: i32) -> Self {
+        let line_total = component.price.saturating_times(quantity);
+        Self {
+            component,
+            quantity,
+            line_total,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RigWithComponents {
+    pub id: Uuid,
+    pub name: String,
+    /// `None` for rigs created before accounts existed
+    pub owner_id: Option<Uuid>,
+    /// Set while the rig can be read by anyone at `/shared/<share_slug>`.
+    /// Only sent to the rig's owner and admins.
+    pub share_slug: Option<String>,
+    /// Rig this one was cloned from, `None` if it was built from scratch or
+    /// the original is gone
+    pub parent_rig_id: Option<Uuid>,
+    pub components: Vec<RigComponentItem>,
+    pub total_price: Money,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CloneRigData {
+    pub name: String,
+    /// Replace the original's components in every slot they belong to;
+    /// slots without overrides keep the original's components
+    #[serde(default)]
+    pub components: Vec<RigComponentData>,
+    #[serde(default)]
+    pub allow_incompatible: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RenameRigData {
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ShareRigData {
+    /// `true` shares the rig (keeping its link if it already is), `false`
+    /// revokes its link
+    pub shared: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct UpdateRigComponentsData {
+    pub components: Vec<RigComponentData>,
+    #[serde(default)]
+    pub allow_incompatible: bool,
+}
+
+/// A rig component priced at some past date. `price` is `None` when the
+/// component had no price yet at that date.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ValuationItem {
+    pub component_id: Uuid,
+    pub manufacturer: String,
+    pub model: String,
+    pub slot: String,
+    pub quantity: i32,
+    pub price: Option<Money>,
+    pub line_total: Option<Money>,
+    pub current_price: Money,
+}
+
+impl ValuationItem {
+    pub fn new(component: Component, quantity: i32, price: Option<Money>) -> Self {
+        let Component { id, manufacturer, model, slot, price: current_price, .. } = component;
+        let line_total = price.as_ref().map(|price| price.saturating_times(quantity));
+
+        Self {
+            component_id: id,
+            manufacturer,
+            model,
+            slot,
+            quantity,
+            price,
+            line_total,
+            current_price,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RigValuation {
+    pub id: Uuid,
+    pub name: String,
+    pub at: Date,
+    pub components: Vec<ValuationItem>,
+    pub total_price: Money,
+    /// Whether every component had a price at that date
+    pub complete: bool,
+}
+
+impl RigValuation {
+    /// `components` must already be priced in `currency`
+    pub fn new(rig: Rig, at: Date, components: Vec<ValuationItem>, currency: &str) -> Self {
+        let Rig { id, name, .. } = rig;
+        let total_price = Money::saturating_sum(currency, components.iter().filter_map(|item| item.line_total.as_ref()));
+        let complete = components
+            .iter()
+            .all(|item| item.price.is_some());
+
+        Self {
+            id,
+            name,
+            at,
+            components,
+            total_price,
+            complete,
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "diesel",
+    derive(diesel::Queryable, diesel::Insertable, diesel::Identifiable, diesel::Selectable, diesel::AsChangeset),
+    diesel(table_name = crate::schema::slots),
+    diesel(primary_key(key)),
+    diesel(treat_none_as_null = true),
+)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Slot {
+    pub key: String,
+    pub name: String,
+    pub icon: String,
+    pub min_per_rig: i32,
+    /// `None` means a rig may hold any number of components of this slot
+    pub max_per_rig: Option<i32>,
+    pub position: i32,
+}
+
+impl Slot {
+    pub fn allows_many(&self) -> bool {
+        self.max_per_rig != Some(1)
+    }
+
+    /// Whether a rig may hold `count` components of this slot
+    pub fn accepts(&self, count: i32) -> bool {
+        count >= self.min_per_rig && self.max_per_rig.map_or(true, |max| count <= max)
+    }
+}
+
+#[cfg(feature = "leptos")]
+impl leptos::IntoView for Slot {
+    fn into_view(self) -> leptos::View {
+        use leptos::view;
+
+        let icon = match self.icon.as_str() {
+            "cpu" => icondata::BsCpu,
+            "gpu" => icondata::BsGpuCard,
+            "memory" => icondata::BsMemory,
+            "storage" => icondata::BsDeviceHdd,
+            "motherboard" => icondata::BsMotherboard,
+            "psu" => icondata::BsPlug,
+            "cooler" => icondata::BsFan,
+            "case" => icondata::BsPc,
+            _ => icondata::BsBox,
+        };
+
+        view! {
+            <thaw::Icon icon />
+        }.into_view()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct UpdateSlotData {
+    pub name: String,
+    pub icon: String,
+    #[serde(default)]
+    pub min_per_rig: i32,
+    #[serde(default)]
+    pub max_per_rig: Option<i32>,
+    #[serde(default)]
+    pub position: i32,
+}
+
+impl UpdateSlotData {
+    pub fn into_slot(self, key: String) -> Slot {
+        let UpdateSlotData { name, icon, min_per_rig, max_per_rig, position } = self;
+        Slot {
+            key,
+            name,
+            icon,
+            min_per_rig,
+            max_per_rig,
+            position,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CreateSlotData {
+    pub key: String,
+    #[serde(flatten)]
+    pub slot: UpdateSlotData,
+}
+
+impl From<CreateSlotData> for Slot {
+    fn from(value: CreateSlotData) -> Self {
+        let CreateSlotData { key, slot } = value;
+        slot.into_slot(key)
+    }
+}
@@ -0,0 +1,84 @@
+use serde::{Deserialize, Serialize};
+
+/// Maps a fieldless enum to a lowercase `TEXT` value, for columns that hold
+/// them or arrays of them (diesel-async can't bind arrays of Postgres enums).
+/// The value must match the enum's serde name, so the JSON, query and CSV
+/// forms are the same as the stored one.
+macro_rules! text_enum {
+    ($name:ident { $($variant:ident => $value:literal),+ $(,)? }) => {
+        impl $name {
+            pub fn as_str(&self) -> &'static str {
+                match self {
+                    $(Self::$variant => $value,)+
+                }
+            }
+        }
+
+        #[cfg(feature = "diesel")]
+        impl diesel::serialize::ToSql<diesel::sql_types::Text, diesel::pg::Pg> for $name {
+            fn to_sql<'b>(&'b self, out: &mut diesel::serialize::Output<'b, '_, diesel::pg::Pg>) -> diesel::serialize::Result {
+                std::io::Write::write_all(out, self.as_str().as_bytes())?;
+                Ok(diesel::serialize::IsNull::No)
+            }
+        }
+
+        #[cfg(feature = "diesel")]
+        impl diesel::deserialize::FromSql<diesel::sql_types::Text, diesel::pg::Pg> for $name {
+            fn from_sql(bytes: diesel::pg::PgValue<'_>) -> diesel::deserialize::Result<Self> {
+                match bytes.as_bytes() {
+                    $(value if value == $value.as_bytes() => Ok(Self::$variant),)+
+                    value => Err(format!("unknown {}: {}", stringify!($name), String::from_utf8_lossy(value)).into()),
+                }
+            }
+        }
+    };
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "diesel", derive(diesel::AsExpression, diesel::FromSqlRow), diesel(sql_type = diesel::sql_types::Text))]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema), schema(rename_all = "lowercase"))]
+pub enum MemoryGeneration {
+    Ddr3,
+    Ddr4,
+    Ddr5,
+}
+
+text_enum!(MemoryGeneration {
+    Ddr3 => "ddr3",
+    Ddr4 => "ddr4",
+    Ddr5 => "ddr5",
+});
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "diesel", derive(diesel::AsExpression, diesel::FromSqlRow), diesel(sql_type = diesel::sql_types::Text))]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema), schema(rename_all = "lowercase"))]
+pub enum Interface {
+    Pcie,
+    Nvme,
+    Sata,
+}
+
+text_enum!(Interface {
+    Pcie => "pcie",
+    Nvme => "nvme",
+    Sata => "sata",
+});
+
+/// What a user may do, each role allowing everything the ones before it do
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "diesel", derive(diesel::AsExpression, diesel::FromSqlRow), diesel(sql_type = diesel::sql_types::Text))]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema), schema(rename_all = "lowercase"))]
+pub enum Role {
+    User,
+    Editor,
+    Admin,
+}
+
+text_enum!(Role {
+    User => "user",
+    Editor => "editor",
+    Admin => "admin",
+});
@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::types::Role;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(
+    feature = "diesel",
+    derive(diesel::Queryable, diesel::Insertable, diesel::Identifiable, diesel::Selectable),
+    diesel(table_name = crate::schema::users),
+)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct User {
+    pub id: Uuid,
+    pub email: String,
+    /// Argon2 PHC string, never sent to clients
+    #[serde(skip)]
+    pub password_hash: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    pub role: Role,
+}
+
+impl User {
+    pub fn new(email: String, password_hash: String) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            email,
+            password_hash,
+            created_at: OffsetDateTime::now_utc(),
+            role: Role::User,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CredentialsData {
+    pub email: String,
+    pub password: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SessionData {
+    /// Sent back as `Authorization: Bearer <token>`
+    pub token: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub expires_at: OffsetDateTime,
+    pub user: User,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct UpdateRoleData {
+    pub role: Role,
+}
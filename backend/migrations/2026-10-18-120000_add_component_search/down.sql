@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS "components_search_idx";
+ALTER TABLE "components" DROP COLUMN IF EXISTS "search";
@@ -0,0 +1,10 @@
+-- Your SQL goes here
+
+-- Punctuation is replaced by spaces so that model numbers such as "RTX-4070"
+-- are indexed as separate words instead of signed numbers
+ALTER TABLE "components" ADD COLUMN "search" TSVECTOR NOT NULL GENERATED ALWAYS AS (
+	setweight(to_tsvector('simple', regexp_replace("model", '[^[:alnum:]]+', ' ', 'g')), 'A') ||
+	setweight(to_tsvector('simple', regexp_replace("manufacturer", '[^[:alnum:]]+', ' ', 'g')), 'B')
+) STORED;
+
+CREATE INDEX "components_search_idx" ON "components" USING GIN ("search");
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE "components"
+	DROP COLUMN IF EXISTS "socket",
+	DROP COLUMN IF EXISTS "memory_generations",
+	DROP COLUMN IF EXISTS "interfaces";
@@ -0,0 +1,10 @@
+-- Your SQL goes here
+
+-- Plain text arrays are used instead of enum arrays as diesel-async can't
+-- bind arrays of custom types
+ALTER TABLE "components"
+	ADD COLUMN "socket" VARCHAR,
+	ADD COLUMN "memory_generations" TEXT[] NOT NULL DEFAULT '{}'
+		CHECK ("memory_generations" <@ ARRAY['ddr3', 'ddr4', 'ddr5']),
+	ADD COLUMN "interfaces" TEXT[] NOT NULL DEFAULT '{}'
+		CHECK ("interfaces" <@ ARRAY['pcie', 'nvme', 'sata']);
@@ -0,0 +1,8 @@
+-- This file should undo anything in `up.sql`
+CREATE TYPE slot AS ENUM ('cpu', 'gpu', 'memory', 'storage');
+
+ALTER TABLE "components"
+	DROP CONSTRAINT IF EXISTS "components_slot_fkey",
+	ALTER COLUMN "slot" TYPE SLOT USING "slot"::SLOT;
+
+DROP TABLE IF EXISTS "slots";
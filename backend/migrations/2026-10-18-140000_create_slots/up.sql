@@ -0,0 +1,21 @@
+-- Your SQL goes here
+CREATE TABLE "slots"(
+	"key" VARCHAR NOT NULL PRIMARY KEY,
+	"name" VARCHAR NOT NULL,
+	"icon" VARCHAR NOT NULL,
+	"min_per_rig" INT4 NOT NULL DEFAULT 0 CHECK ("min_per_rig" >= 0),
+	"max_per_rig" INT4 CHECK ("max_per_rig" IS NULL OR "max_per_rig" >= GREATEST("min_per_rig", 1)),
+	"position" INT4 NOT NULL DEFAULT 0
+);
+
+INSERT INTO "slots"("key", "name", "icon", "min_per_rig", "max_per_rig", "position") VALUES
+	('cpu', 'CPU', 'cpu', 1, 1, 0),
+	('gpu', 'GPU', 'gpu', 1, 1, 1),
+	('memory', 'Memory', 'memory', 1, 1, 2),
+	('storage', 'Storage', 'storage', 1, 1, 3);
+
+ALTER TABLE "components"
+	ALTER COLUMN "slot" TYPE VARCHAR USING "slot"::TEXT,
+	ADD CONSTRAINT "components_slot_fkey" FOREIGN KEY ("slot") REFERENCES "slots"("key") ON UPDATE CASCADE;
+
+DROP TYPE slot;
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+UPDATE "slots" SET "max_per_rig" = 1 WHERE "key" IN ('memory', 'storage');
+
+ALTER TABLE "rig_components" DROP COLUMN IF EXISTS "quantity";
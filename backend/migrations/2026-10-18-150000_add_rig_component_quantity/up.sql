@@ -0,0 +1,7 @@
+-- Your SQL goes here
+ALTER TABLE "rig_components" ADD COLUMN "quantity" INT4 NOT NULL DEFAULT 1 CHECK ("quantity" > 0);
+
+-- Slot limits count units, so the default memory and storage slots need room
+-- for more than a single stick or drive
+UPDATE "slots" SET "max_per_rig" = 4 WHERE "key" = 'memory' AND "max_per_rig" = 1;
+UPDATE "slots" SET "max_per_rig" = NULL WHERE "key" = 'storage' AND "max_per_rig" = 1;
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP TRIGGER record_component_price ON "components";
+DROP FUNCTION record_component_price();
+DROP TABLE "component_prices";
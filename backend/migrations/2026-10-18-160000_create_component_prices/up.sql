@@ -0,0 +1,28 @@
+-- Your SQL goes here
+CREATE TABLE "component_prices"(
+	"id" BIGSERIAL NOT NULL PRIMARY KEY,
+	"component_id" UUID NOT NULL REFERENCES components(id) ON DELETE CASCADE,
+	"price" INT4 NOT NULL,
+	"changed_at" TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX "component_prices_component_id_changed_at_idx"
+	ON "component_prices" ("component_id", "changed_at");
+
+-- Current prices become the first entry of every history
+INSERT INTO "component_prices" ("component_id", "price")
+SELECT "id", "price" FROM "components";
+
+-- Every price a component gets, on insert or update, is recorded
+CREATE FUNCTION record_component_price() RETURNS trigger AS $$
+BEGIN
+    IF TG_OP = 'INSERT' OR NEW.price IS DISTINCT FROM OLD.price THEN
+        INSERT INTO component_prices (component_id, price) VALUES (NEW.id, NEW.price);
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER record_component_price
+	AFTER INSERT OR UPDATE OF price ON "components"
+	FOR EACH ROW EXECUTE PROCEDURE record_component_price();
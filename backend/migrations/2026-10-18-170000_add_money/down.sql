@@ -0,0 +1,30 @@
+-- This file should undo anything in `up.sql`
+DROP TRIGGER record_component_price ON "components";
+
+CREATE OR REPLACE FUNCTION record_component_price() RETURNS trigger AS $$
+BEGIN
+    IF TG_OP = 'INSERT' OR NEW.price IS DISTINCT FROM OLD.price THEN
+        INSERT INTO component_prices (component_id, price) VALUES (NEW.id, NEW.price);
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Prices in other currencies are brought back to reais at the stored rates
+UPDATE "component_prices" SET "price_cents" = "price_cents" * "rate_micros" / 1000000
+FROM "exchange_rates" WHERE "exchange_rates"."currency" = "component_prices"."currency";
+ALTER TABLE "component_prices" DROP COLUMN "currency";
+ALTER TABLE "component_prices" ALTER COLUMN "price_cents" TYPE INT4 USING round("price_cents" / 100.0);
+ALTER TABLE "component_prices" RENAME COLUMN "price_cents" TO "price";
+
+UPDATE "components" SET "price_cents" = "price_cents" * "rate_micros" / 1000000
+FROM "exchange_rates" WHERE "exchange_rates"."currency" = "components"."currency";
+ALTER TABLE "components" DROP COLUMN "currency";
+ALTER TABLE "components" ALTER COLUMN "price_cents" TYPE INT4 USING round("price_cents" / 100.0);
+ALTER TABLE "components" RENAME COLUMN "price_cents" TO "price";
+
+CREATE TRIGGER record_component_price
+	AFTER INSERT OR UPDATE OF price ON "components"
+	FOR EACH ROW EXECUTE PROCEDURE record_component_price();
+
+DROP TABLE "exchange_rates";
@@ -0,0 +1,44 @@
+-- Your SQL goes here
+CREATE TABLE "exchange_rates"(
+	"currency" VARCHAR(3) NOT NULL PRIMARY KEY CHECK ("currency" ~ '^[A-Z]{3}$'),
+	-- Value of one unit of the currency in the base currency (BRL), in millionths
+	"rate_micros" INT8 NOT NULL CHECK ("rate_micros" > 0),
+	"updated_at" TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+SELECT diesel_manage_updated_at('exchange_rates');
+
+INSERT INTO "exchange_rates" ("currency", "rate_micros") VALUES
+	('BRL', 1000000),
+	('USD', 5400000),
+	('EUR', 5900000);
+
+DROP TRIGGER record_component_price ON "components";
+
+-- Prices move from whole reais to cents of an explicit currency
+ALTER TABLE "components" RENAME COLUMN "price" TO "price_cents";
+ALTER TABLE "components" ALTER COLUMN "price_cents" TYPE INT8 USING "price_cents" * 100;
+ALTER TABLE "components" ADD COLUMN "currency" VARCHAR(3) NOT NULL DEFAULT 'BRL' REFERENCES exchange_rates(currency);
+ALTER TABLE "components" ALTER COLUMN "currency" DROP DEFAULT;
+
+ALTER TABLE "component_prices" RENAME COLUMN "price" TO "price_cents";
+ALTER TABLE "component_prices" ALTER COLUMN "price_cents" TYPE INT8 USING "price_cents" * 100;
+ALTER TABLE "component_prices" ADD COLUMN "currency" VARCHAR(3) NOT NULL DEFAULT 'BRL';
+ALTER TABLE "component_prices" ALTER COLUMN "currency" DROP DEFAULT;
+
+CREATE OR REPLACE FUNCTION record_component_price() RETURNS trigger AS $$
+BEGIN
+    IF TG_OP = 'INSERT'
+        OR NEW.price_cents IS DISTINCT FROM OLD.price_cents
+        OR NEW.currency IS DISTINCT FROM OLD.currency
+    THEN
+        INSERT INTO component_prices (component_id, price_cents, currency)
+        VALUES (NEW.id, NEW.price_cents, NEW.currency);
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER record_component_price
+	AFTER INSERT OR UPDATE OF price_cents, currency ON "components"
+	FOR EACH ROW EXECUTE PROCEDURE record_component_price();
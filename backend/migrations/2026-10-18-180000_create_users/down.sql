@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE "rigs" DROP COLUMN "owner_id";
+DROP TABLE "sessions";
+DROP TABLE "users";
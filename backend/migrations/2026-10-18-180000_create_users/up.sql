@@ -0,0 +1,22 @@
+-- Your SQL goes here
+CREATE TABLE "users"(
+	"id" UUID NOT NULL PRIMARY KEY,
+	-- Stored lowercased
+	"email" VARCHAR NOT NULL UNIQUE,
+	"password_hash" VARCHAR NOT NULL,
+	"created_at" TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE TABLE "sessions"(
+	-- SHA-256 of the token handed to the client, which is never stored
+	"token_hash" VARCHAR NOT NULL PRIMARY KEY,
+	"user_id" UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+	"created_at" TIMESTAMPTZ NOT NULL DEFAULT now(),
+	"expires_at" TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX "sessions_user_id_idx" ON "sessions" ("user_id");
+
+-- Rigs created before accounts existed have no owner
+ALTER TABLE "rigs" ADD COLUMN "owner_id" UUID REFERENCES users(id) ON DELETE SET NULL;
+CREATE INDEX "rigs_owner_id_idx" ON "rigs" ("owner_id");
@@ -0,0 +1,3 @@
+-- Your SQL goes here
+ALTER TABLE "users" ADD COLUMN "role" VARCHAR NOT NULL DEFAULT 'user'
+	CHECK ("role" IN ('admin', 'editor', 'user'));
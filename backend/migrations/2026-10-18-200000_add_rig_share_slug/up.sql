@@ -0,0 +1,3 @@
+-- Your SQL goes here
+-- Set while the rig is shared through a public link
+ALTER TABLE "rigs" ADD COLUMN "share_slug" VARCHAR UNIQUE;
@@ -0,0 +1,4 @@
+-- Your SQL goes here
+-- Rig a rig was cloned from, forgotten if that rig is deleted
+ALTER TABLE "rigs" ADD COLUMN "parent_rig_id" UUID REFERENCES "rigs" ("id") ON DELETE SET NULL;
+CREATE INDEX "rigs_parent_rig_id_idx" ON "rigs" ("parent_rig_id");
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE "rig_revision_components";
+DROP TABLE "rig_revisions";
@@ -0,0 +1,34 @@
+-- Your SQL goes here
+CREATE TABLE "rig_revisions"(
+	"rig_id" UUID NOT NULL REFERENCES rigs(id) ON DELETE CASCADE,
+	"number" INT4 NOT NULL CHECK ("number" > 0),
+	"name" VARCHAR NOT NULL,
+	"created_at" TIMESTAMPTZ NOT NULL DEFAULT now(),
+	PRIMARY KEY ("rig_id", "number")
+);
+
+-- Components are copied rather than referenced so revisions stay as they
+-- were after the catalog changes or drops them
+CREATE TABLE "rig_revision_components"(
+	"rig_id" UUID NOT NULL,
+	"number" INT4 NOT NULL,
+	"component_id" UUID NOT NULL,
+	"manufacturer" VARCHAR NOT NULL,
+	"model" VARCHAR NOT NULL,
+	"slot" VARCHAR NOT NULL,
+	"quantity" INT4 NOT NULL,
+	"price_cents" INT8 NOT NULL,
+	"currency" VARCHAR(3) NOT NULL REFERENCES exchange_rates(currency),
+	PRIMARY KEY ("rig_id", "number", "component_id"),
+	FOREIGN KEY ("rig_id", "number") REFERENCES rig_revisions("rig_id", "number") ON DELETE CASCADE
+);
+
+-- Existing rigs start their history as they are now
+INSERT INTO "rig_revisions" ("rig_id", "number", "name")
+SELECT "id", 1, "name" FROM "rigs";
+
+INSERT INTO "rig_revision_components"
+	("rig_id", "number", "component_id", "manufacturer", "model", "slot", "quantity", "price_cents", "currency")
+SELECT rc."rig_id", 1, c."id", c."manufacturer", c."model", c."slot", rc."quantity", c."price_cents", c."currency"
+FROM "rig_components" rc
+JOIN "components" c ON c."id" = rc."component_id";
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+-- Merged duplicates stay merged
+ALTER TABLE "components" DROP CONSTRAINT "components_manufacturer_model_key";
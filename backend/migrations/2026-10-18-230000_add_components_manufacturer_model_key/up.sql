@@ -0,0 +1,34 @@
+-- Your SQL goes here
+-- Imports upsert components by manufacturer and model, so the pair must be
+-- unique. Catalogs may already list a part more than once, which this finds:
+--
+--   SELECT "manufacturer", "model", count(*) FROM "components"
+--   GROUP BY "manufacturer", "model" HAVING count(*) > 1;
+--
+-- Every duplicate is merged into the copy with the lowest id: rigs listing a
+-- duplicate list that copy instead, adding up quantities when they listed
+-- both. The duplicates' price histories are deleted along with them, and
+-- revisions keep the ids they were taken with.
+CREATE TEMPORARY TABLE "duplicate_components" ON COMMIT DROP AS
+SELECT "id", "kept_id"
+FROM (
+	SELECT "id", first_value("id") OVER (PARTITION BY "manufacturer", "model" ORDER BY "id") AS "kept_id"
+	FROM "components"
+) AS "copies"
+WHERE "id" <> "kept_id";
+
+INSERT INTO "rig_components" ("rig_id", "component_id", "quantity")
+SELECT rc."rig_id", d."kept_id", sum(rc."quantity")
+FROM "rig_components" rc
+JOIN "duplicate_components" d ON d."id" = rc."component_id"
+GROUP BY rc."rig_id", d."kept_id"
+ON CONFLICT ("rig_id", "component_id")
+	DO UPDATE SET "quantity" = "rig_components"."quantity" + EXCLUDED."quantity";
+
+DELETE FROM "rig_components"
+WHERE "component_id" IN (SELECT "id" FROM "duplicate_components");
+
+DELETE FROM "components"
+WHERE "id" IN (SELECT "id" FROM "duplicate_components");
+
+ALTER TABLE "components" ADD CONSTRAINT "components_manufacturer_model_key" UNIQUE ("manufacturer", "model");
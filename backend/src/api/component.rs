@@ -1,43 +1,779 @@
-use rocket::{fairing::AdHoc, get, post, routes, serde::json::Json, Responder};
-use rocket_db_pools::{Connection, diesel::QueryResult};
+use std::collections::{HashMap, HashSet};
+
+use api_types::{
+    component::{Component, ComponentPrice, ComponentQuery, CreateComponentData, ImportReport, ImportRowReport, ImportStatus, UpdateComponentData},
+    export::ExportFormat,
+    page::{Page, DEFAULT_PER_PAGE},
+};
+use rocket::{
+    data::{Data, Limits, ToByteUnit},
+    delete, fairing::AdHoc, form, get,
+    futures::StreamExt,
+    http::{ContentType, Status},
+    patch, post, put,
+    response::stream::TextStream,
+    routes,
+    serde::json::Json,
+    State,
+};
 use uuid::Uuid;
 
-use crate::{database::Db, dto::component::CreateComponentData, models::component::Component, repository::component};
+use crate::{
+    api::currency::converter,
+    auth::{Admin, RequireRole},
+    error::{ApiError, ApiResult},
+    export::{opened, Encoder, ExportFormatExt},
+    import::{self as catalog, CsvComponent, ImportFormat},
+    money::Convert,
+    repository::ComponentRepository,
+    validation::{self, ValidationErrors},
+};
 
 pub fn stage() -> AdHoc {
     AdHoc::on_ignite("Component Routes", |rocket| async {
-        rocket.mount("/components", routes![detail, list, create])
+        rocket.mount("/components", routes![detail, prices, list, search, export, create, import, update, patch, delete])
     })
 }
 
+#[utoipa::path(
+    context_path = "/components",
+    tag = "components",
+    operation_id = "get_component",
+    params(
+        ("id" = Uuid, Path, description = "Component id"),
+        ("currency" = Option<String>, Query, description = "Currency to convert prices to, left as stored without it"),
+    ),
+    responses(
+        (status = 200, description = "The component", body = Component),
+        (status = 400, description = "Malformed id", body = ApiError),
+        (status = 404, description = "No such component", body = ApiError),
+        (status = 422, description = "Unknown `currency`", body = ApiError),
+    ),
+)]
+#[get("/<id>?<currency>")]
+pub async fn detail(id: &str, currency: Option<&str>, components: &State<Box<dyn ComponentRepository>>) -> ApiResult<Json<Component>> {
+    let id = Uuid::parse_str(id)?;
+
+    let component = components.get_component(id).await?;
 
-#[derive(Responder)]
-pub enum GetError {
-    #[response(status = 400)]
-    InvalidId(String),
-    #[response(status = 404)]
-    NotFound(()),
+    Ok(Json(match currency {
+        Some(currency) => component.converted(&converter(Some(currency), components.as_ref()).await?)?,
+        None => component,
+    }))
 }
 
-#[get("/<id>")]
-pub async fn detail(id: &str, mut db: Connection<Db>) -> Result<Json<Component>, GetError> {
-    let id = Uuid::parse_str(id)
-        .map_err(|e| GetError::InvalidId(format!("{e}")))?;
+#[utoipa::path(
+    context_path = "/components",
+    tag = "components",
+    operation_id = "list_component_prices",
+    params(
+        ("id" = Uuid, Path, description = "Component id"),
+    ),
+    responses(
+        (status = 200, description = "Every price the component had, oldest first", body = Vec<ComponentPrice>),
+        (status = 400, description = "Malformed id", body = ApiError),
+        (status = 404, description = "No such component", body = ApiError),
+    ),
+)]
+#[get("/<id>/prices")]
+pub async fn prices(id: &str, components: &State<Box<dyn ComponentRepository>>) -> ApiResult<Json<Vec<ComponentPrice>>> {
+    let id = Uuid::parse_str(id)?;
 
-    Ok(Json(
-        component::get_component(id, &mut db)
-        .await
-        .map_err(|_| GetError::NotFound(()))?
-    ))
+    Ok(Json(components.list_component_prices(id).await?))
+}
+
+#[utoipa::path(
+    context_path = "/components",
+    tag = "components",
+    operation_id = "list_components",
+    params(
+        ComponentQuery,
+    ),
+    responses(
+        (status = 200, description = "A page of components", body = Page<Component>),
+        (status = 422, description = "Negative or inverted price bounds, `page` or `per_page` out of range, or an unknown `currency`", body = ApiError),
+    ),
+)]
+#[get("/?<query..>")]
+pub async fn list(query: Result<ComponentQuery, form::Errors<'_>>, components: &State<Box<dyn ComponentRepository>>) -> ApiResult<Json<Page<Component>>> {
+    let query = query.map_err(ValidationErrors::from)?;
+
+    let mut errors = ValidationErrors::default();
+    validation::component::validate_query(&query, &mut errors);
+    errors.into_result()?;
+
+    let converter = converter(query.currency.as_deref(), components.as_ref()).await?;
+    let page = components.list_components(&query, converter.rate()).await?;
+
+    Ok(Json(match query.currency {
+        Some(_) => page.try_map(|component| component.converted(&converter))?,
+        None => page,
+    }))
 }
 
-#[get("/")]
-pub async fn list(mut db: Connection<Db>) -> QueryResult<Json<Vec<Component>>> {
-    Ok(Json(component::list_components(&mut db).await?))
+#[utoipa::path(
+    context_path = "/components",
+    tag = "components",
+    operation_id = "search_components",
+    params(
+        ("q" = String, Query, description = "Words to look for in the manufacturer and model"),
+        ("limit" = Option<i64>, Query, description = "At most this many results, 20 by default"),
+        ("currency" = Option<String>, Query, description = "Currency to convert prices to, left as stored without it"),
+    ),
+    responses(
+        (status = 200, description = "Matching components, best first", body = Vec<Component>),
+        (status = 422, description = "Empty `q`, `limit` out of range or an unknown `currency`", body = ApiError),
+    ),
+)]
+#[get("/search?<q>&<limit>&<currency>")]
+pub async fn search(q: &str, limit: Option<i64>, currency: Option<&str>, components: &State<Box<dyn ComponentRepository>>) -> ApiResult<Json<Vec<Component>>> {
+    let limit = limit.unwrap_or(DEFAULT_PER_PAGE);
+
+    let mut errors = ValidationErrors::default();
+    validation::component::validate_search(q, limit, &mut errors);
+    errors.into_result()?;
+
+    let found = components.search_components(q, limit).await?;
+
+    Ok(Json(match currency {
+        Some(currency) => {
+            let converter = converter(Some(currency), components.as_ref()).await?;
+            found
+                .into_iter()
+                .map(|component| component.converted(&converter))
+                .collect::<ApiResult<_>>()?
+        },
+        None => found,
+    }))
 }
 
+/// The whole catalog, streamed as it's read. CSV comes in the shape imports
+/// take; `currency` converts prices like it does for listings.
+#[utoipa::path(
+    context_path = "/components",
+    tag = "components",
+    operation_id = "export_components",
+    params(
+        ("format" = Option<ExportFormat>, Query, description = "JSON by default"),
+        ("currency" = Option<String>, Query, description = "Currency to convert prices to, left as stored without it"),
+    ),
+    responses(
+        (status = 200, description = "Every component, streamed. Cut short by an error, JSON is left unclosed, NDJSON ends with an `export_failed` error line and CSV with a `# export_failed` line", content((String = "text/csv"), (Vec<Component> = "application/json"), (String = "application/x-ndjson"))),
+        (status = 422, description = "Unknown `format` or `currency`", body = ApiError),
+        (status = 500, description = "The export failed before its first record", body = ApiError),
+    ),
+)]
+#[get("/export?<format>&<currency>")]
+pub async fn export(format: form::Result<'_, ExportFormat>, currency: Option<&str>, components: &State<Box<dyn ComponentRepository>>) -> ApiResult<(ContentType, TextStream![String])> {
+    let format = ExportFormat::from_query(format)?;
+    let converter = match currency {
+        Some(currency) => Some(converter(Some(currency), components.as_ref()).await?),
+        None => None,
+    };
+    let mut rows = opened(components.stream_components().await?).await?;
+
+    let mut encoder = Encoder::new(format);
+    let opening = encoder.open::<CsvComponent>().map_err(|err| {
+        rocket::error!("could not export components: {err}");
+        ApiError::internal()
+    })?;
+    let body = TextStream! {
+        yield opening;
 
+        while let Some(component) = rows.next().await {
+            let component = match component {
+                Ok(component) => component,
+                Err(err) => {
+                    rocket::error!("could not export components: {err:?}");
+                    yield encoder.fail();
+                    return;
+                },
+            };
+            let component = match &converter {
+                Some(converter) => match component.converted(converter) {
+                    Ok(component) => component,
+                    Err(err) => {
+                        rocket::error!("could not export components: {err:?}");
+                        yield encoder.fail();
+                        return;
+                    },
+                },
+                None => component,
+            };
+
+            let piece = match encoder.format() {
+                ExportFormat::Csv => encoder.csv_record(&CsvComponent::from(component)).map_err(|err| err.to_string()),
+                ExportFormat::Json | ExportFormat::Ndjson => encoder.json_record(&component).map_err(|err| err.to_string()),
+            };
+            match piece {
+                Ok(piece) => yield piece,
+                Err(err) => {
+                    rocket::error!("could not export components: {err}");
+                    yield encoder.fail();
+                    return;
+                },
+            }
+        }
+
+        yield encoder.close();
+    };
+
+    Ok((format.content_type(), body))
+}
+
+#[utoipa::path(
+    context_path = "/components",
+    tag = "components",
+    operation_id = "create_component",
+    responses(
+        (status = 200, description = "The created component", body = Component),
+        (status = 401, description = "Missing or invalid bearer token", body = ApiError),
+        (status = 403, description = "Only admins may do it", body = ApiError),
+        (status = 422, description = "Negative price or one over the maximum, or a slot or currency that doesn't exist", body = ApiError),
+    ),
+    security(("bearer_auth" = [])),
+)]
 #[post("/", data = "<component>")]
-pub async fn create(component: Json<CreateComponentData>, mut db: Connection<Db>) -> QueryResult<Json<Component>> {
-    Ok(Json(component::create_component(component.0.into(), &mut db).await?))
+pub async fn create(_admin: RequireRole<Admin>, component: Json<CreateComponentData>, components: &State<Box<dyn ComponentRepository>>) -> ApiResult<Json<Component>> {
+    let mut errors = ValidationErrors::default();
+    validation::component::validate_price(&component.price, &mut errors);
+    errors.into_result()?;
+
+    Ok(Json(components.create_component(component.0.into()).await?))
+}
+
+/// Creates or updates, by manufacturer and model, every valid component of a
+/// CSV or JSON catalog at once. `dry_run=true` reports what would happen
+/// without writing anything.
+#[utoipa::path(
+    context_path = "/components",
+    tag = "components",
+    operation_id = "import_components",
+    params(
+        ("dry_run" = Option<bool>, Query, description = "Only report what would happen"),
+    ),
+    request_body(content((String = "text/csv"), (Vec<CreateComponentData> = "application/json")), description = "A catalog of components"),
+    responses(
+        (status = 200, description = "What happened to every row", body = ImportReport),
+        (status = 400, description = "The catalog could not be read", body = ApiError),
+        (status = 401, description = "Missing or invalid bearer token", body = ApiError),
+        (status = 403, description = "Only admins may do it", body = ApiError),
+        (status = 413, description = "The catalog is over the import size limit", body = ApiError),
+        (status = 415, description = "The catalog is neither CSV nor JSON", body = ApiError),
+    ),
+    security(("bearer_auth" = [])),
+)]
+#[post("/import?<dry_run>", data = "<upload>")]
+pub async fn import(
+    _admin: RequireRole<Admin>,
+    content_type: Option<&ContentType>,
+    limits: &Limits,
+    upload: Data<'_>,
+    dry_run: Option<bool>,
+    components: &State<Box<dyn ComponentRepository>>,
+) -> ApiResult<Json<ImportReport>> {
+    let dry_run = dry_run.unwrap_or_default();
+    let format = match content_type {
+        Some(content_type) if content_type.is_csv() => ImportFormat::Csv,
+        Some(content_type) if content_type.is_json() => ImportFormat::Json,
+        _ => return Err(ApiError::new(Status::UnsupportedMediaType, "unsupported_media_type", "catalogs must be sent as text/csv or application/json")),
+    };
+
+    let body = upload
+        .open(limits.get("import").unwrap_or(4.mebibytes()))
+        .into_string()
+        .await
+        .map_err(|error| ApiError::bad_request(format!("could not read the catalog: {error}")))?;
+    if !body.is_complete() {
+        return Err(ApiError::new(Status::PayloadTooLarge, "payload_too_large", "the catalog is over the import size limit"));
+    }
+
+    let rows = catalog::parse(format, &body)?;
+
+    let slots: HashSet<String> = components.list_slots().await?.into_iter().map(|slot| slot.key).collect();
+    let currencies: HashSet<String> = components.list_rates().await?.into_iter().map(|rate| rate.currency).collect();
+
+    // Only looked up to report on a dry run; writes find the components they
+    // update as they upsert them
+    let existing = if dry_run {
+        let names: Vec<(String, String)> = rows
+            .iter()
+            .flatten()
+            .map(|component| (component.manufacturer.clone(), component.model.clone()))
+            .collect();
+        components.find_component_ids(&names).await?
+    } else {
+        HashMap::new()
+    };
+
+    // Row each manufacturer and model was first accepted at
+    let mut accepted: HashMap<(String, String), usize> = HashMap::new();
+    let mut reports = Vec::with_capacity(rows.len());
+    // Components to write, with the index of their row's report
+    let mut written = Vec::new();
+
+    for (index, parsed) in rows.into_iter().enumerate() {
+        let row = index + 1;
+        let data = match parsed {
+            Ok(data) => data,
+            Err(errors) => {
+                reports.push(ImportRowReport::rejected(row, None, errors.fields));
+                continue;
+            },
+        };
+
+        let mut errors = ValidationErrors::default();
+        validation::component::validate_import(&data, &slots, &currencies, &mut errors);
+
+        let name = (data.manufacturer.clone(), data.model.clone());
+        if let Some(first) = accepted.get(&name) {
+            errors.add("model", format!("duplicates row {first}"));
+        }
+
+        if !errors.is_empty() {
+            for error in &mut errors.fields {
+                error.field = format.field_name(std::mem::take(&mut error.field));
+            }
+            reports.push(ImportRowReport::rejected(row, Some(&data), errors.fields));
+            continue;
+        }
+        accepted.insert(name.clone(), row);
+
+        let (status, id) = if dry_run {
+            match existing.get(&name).copied() {
+                Some(id) => (ImportStatus::Updated, Some(id)),
+                None => (ImportStatus::Created, None),
+            }
+        } else {
+            // Settled once written
+            let component = Component::from(data);
+            let id = component.id;
+            written.push((reports.len(), component));
+            (ImportStatus::Created, Some(id))
+        };
+
+        let (manufacturer, model) = name;
+        reports.push(ImportRowReport {
+            row,
+            status,
+            id,
+            manufacturer: Some(manufacturer),
+            model: Some(model),
+            errors: Vec::new(),
+        });
+    }
+
+    // Written all at once, so a failure leaves the catalog as it was
+    let (indexes, written): (Vec<usize>, Vec<Component>) = written.into_iter().unzip();
+    let stored = components.import_components(written).await?;
+    for (index, component) in indexes.into_iter().zip(stored) {
+        let report = &mut reports[index];
+        if report.id != Some(component.id) {
+            report.status = ImportStatus::Updated;
+            report.id = Some(component.id);
+        }
+    }
+
+    Ok(Json(ImportReport::new(dry_run, reports)))
+}
+
+#[utoipa::path(
+    context_path = "/components",
+    tag = "components",
+    operation_id = "update_component",
+    params(
+        ("id" = Uuid, Path, description = "Component id"),
+    ),
+    responses(
+        (status = 200, description = "The updated component", body = Component),
+        (status = 400, description = "Malformed id", body = ApiError),
+        (status = 401, description = "Missing or invalid bearer token", body = ApiError),
+        (status = 403, description = "Only admins may do it", body = ApiError),
+        (status = 404, description = "No such component", body = ApiError),
+        (status = 422, description = "Negative price or one over the maximum, or a slot or currency that doesn't exist", body = ApiError),
+    ),
+    security(("bearer_auth" = [])),
+)]
+#[put("/<id>", data = "<component>")]
+pub async fn update(_admin: RequireRole<Admin>, id: &str, component: Json<CreateComponentData>, components: &State<Box<dyn ComponentRepository>>) -> ApiResult<Json<Component>> {
+    let id = Uuid::parse_str(id)?;
+
+    let mut errors = ValidationErrors::default();
+    validation::component::validate_price(&component.price, &mut errors);
+    errors.into_result()?;
+
+    let component = Component { id, ..component.0.into() };
+
+    Ok(Json(components.update_component(component).await?))
+}
+
+#[utoipa::path(
+    context_path = "/components",
+    tag = "components",
+    operation_id = "patch_component",
+    params(
+        ("id" = Uuid, Path, description = "Component id"),
+    ),
+    responses(
+        (status = 200, description = "The updated component", body = Component),
+        (status = 400, description = "Malformed id", body = ApiError),
+        (status = 401, description = "Missing or invalid bearer token", body = ApiError),
+        (status = 403, description = "Only admins may do it", body = ApiError),
+        (status = 404, description = "No such component", body = ApiError),
+        (status = 422, description = "Negative price or one over the maximum, or a slot or currency that doesn't exist", body = ApiError),
+    ),
+    security(("bearer_auth" = [])),
+)]
+#[patch("/<id>", data = "<changes>")]
+pub async fn patch(_admin: RequireRole<Admin>, id: &str, changes: Json<UpdateComponentData>, components: &State<Box<dyn ComponentRepository>>) -> ApiResult<Json<Component>> {
+    let id = Uuid::parse_str(id)?;
+
+    let mut errors = ValidationErrors::default();
+    if let Some(price) = &changes.price {
+        validation::component::validate_price(price, &mut errors);
+    }
+    errors.into_result()?;
+
+    Ok(Json(components.patch_component(id, changes.0.into()).await?))
+}
+
+#[utoipa::path(
+    context_path = "/components",
+    tag = "components",
+    operation_id = "delete_component",
+    params(
+        ("id" = Uuid, Path, description = "Component id"),
+    ),
+    responses(
+        (status = 200, description = "The component was deleted"),
+        (status = 400, description = "Malformed id", body = ApiError),
+        (status = 401, description = "Missing or invalid bearer token", body = ApiError),
+        (status = 403, description = "Only admins may do it", body = ApiError),
+        (status = 404, description = "No such component", body = ApiError),
+        (status = 409, description = "The component is part of rigs, listed in `details`", body = ApiError),
+    ),
+    security(("bearer_auth" = [])),
+)]
+#[delete("/<id>")]
+pub async fn delete(_admin: RequireRole<Admin>, id: &str, components: &State<Box<dyn ComponentRepository>>) -> ApiResult<()> {
+    let id = Uuid::parse_str(id)?;
+
+    let rigs = components.delete_component(id).await?;
+    if !rigs.is_empty() {
+        return Err(
+            ApiError::conflict("component is still part of one or more rigs")
+                .with_details(rigs)
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use api_types::{
+        component::{Component, ComponentPrice, ImportReport, ImportStatus},
+        money::Money,
+        page::Page,
+        rig::CreateRigData,
+        types::{Interface, MemoryGeneration, Role},
+    };
+    use rocket::{
+        http::{ContentType, Status},
+        serde::json::{serde_json::json, Value},
+    };
+
+    use crate::{
+        api::testing::{add_component, add_parts, client, log_in},
+        repository::{memory::InMemoryRepository, ComponentRepository},
+    };
+
+    #[rocket::async_test]
+    async fn detail_converts_to_the_currency_asked_for() {
+        let repository = InMemoryRepository::new();
+        let cpu = add_component(&repository, "cpu", "C1", Money::new(540_000, "BRL")).await;
+        let client = client(&repository).await;
+
+        let response = client.get(format!("/components/{}", cpu.id)).dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.into_json::<Component>().await.unwrap().price, Money::new(540_000, "BRL"));
+
+        let response = client.get(format!("/components/{}?currency=USD", cpu.id)).dispatch().await;
+        assert_eq!(response.into_json::<Component>().await.unwrap().price, Money::new(100_000, "USD"));
+
+        let response = client.get(format!("/components/{}?currency=XYZ", cpu.id)).dispatch().await;
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+
+        let response = client.get(format!("/components/{}", uuid::Uuid::new_v4())).dispatch().await;
+        assert_eq!(response.status(), Status::NotFound);
+
+        let response = client.get("/components/not-an-id").dispatch().await;
+        assert_eq!(response.status(), Status::BadRequest);
+    }
+
+    #[rocket::async_test]
+    async fn prices_keep_every_price_change() {
+        let repository = InMemoryRepository::new();
+        let cpu = add_component(&repository, "cpu", "C1", Money::new(100_000, "BRL")).await;
+        let (_, admin) = log_in(&repository, Role::Admin);
+        let client = client(&repository).await;
+
+        client.patch(format!("/components/{}", cpu.id))
+            .header(admin)
+            .json(&json!({ "price": { "amount": 90_000, "currency": "BRL" } }))
+            .dispatch()
+            .await;
+
+        let response = client.get(format!("/components/{}/prices", cpu.id)).dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+        let prices: Vec<Money> = response.into_json::<Vec<ComponentPrice>>().await.unwrap()
+            .into_iter()
+            .map(|price| price.price)
+            .collect();
+        assert_eq!(prices, [Money::new(100_000, "BRL"), Money::new(90_000, "BRL")]);
+
+        let response = client.get(format!("/components/{}/prices", uuid::Uuid::new_v4())).dispatch().await;
+        assert_eq!(response.status(), Status::NotFound);
+    }
+
+    #[rocket::async_test]
+    async fn list_filters_sorts_and_pages() {
+        let repository = InMemoryRepository::new();
+        add_component(&repository, "cpu", "C1", Money::new(300_000, "BRL")).await;
+        add_component(&repository, "cpu", "C2", Money::new(10_000, "USD")).await;
+        add_component(&repository, "cpu", "C3", Money::new(100_000, "BRL")).await;
+        add_component(&repository, "gpu", "G1", Money::new(1_000, "BRL")).await;
+        let client = client(&repository).await;
+
+        let response = client.get("/components?slot=cpu&sort=-price&per_page=2").dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+        let page = response.into_json::<Page<Component>>().await.unwrap();
+        let models: Vec<&str> = page.items.iter().map(|component| component.model.as_str()).collect();
+        assert_eq!(models, ["C1", "C3"]);
+        assert_eq!((page.total, page.total_pages), (3, 2));
+
+        // USD 100.00 is BRL 540.00
+        let response = client.get("/components?min_price=50000&max_price=60000").dispatch().await;
+        let page = response.into_json::<Page<Component>>().await.unwrap();
+        let models: Vec<&str> = page.items.iter().map(|component| component.model.as_str()).collect();
+        assert_eq!(models, ["C2"]);
+
+        let response = client.get("/components?per_page=0").dispatch().await;
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+
+        let response = client.get(format!("/components?page={}", i64::MAX)).dispatch().await;
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+    }
+
+    #[rocket::async_test]
+    async fn search_matches_word_prefixes() {
+        let repository = InMemoryRepository::new();
+        add_component(&repository, "cpu", "Ryzen 7 7800X3D", Money::new(100_000, "BRL")).await;
+        add_component(&repository, "cpu", "Ryzen 5 7600", Money::new(100_000, "BRL")).await;
+        let client = client(&repository).await;
+
+        let response = client.get("/components/search?q=ryz%207").dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+        let found = response.into_json::<Vec<Component>>().await.unwrap();
+        let models: Vec<&str> = found.iter().map(|component| component.model.as_str()).collect();
+        assert_eq!(models, ["Ryzen 5 7600", "Ryzen 7 7800X3D"]);
+
+        let response = client.get("/components/search?q=").dispatch().await;
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+    }
+
+    #[rocket::async_test]
+    async fn export_streams_the_catalog() {
+        let repository = InMemoryRepository::new();
+        add_parts(&repository).await;
+        let client = client(&repository).await;
+
+        let response = client.get("/components/export?currency=USD").dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.content_type(), Some(ContentType::JSON));
+        let exported = response.into_json::<Vec<Component>>().await.unwrap();
+        assert_eq!(exported.len(), 4);
+        assert!(exported.iter().all(|component| component.price.currency == "USD"));
+
+        let response = client.get("/components/export?format=csv").dispatch().await;
+        assert_eq!(response.content_type(), Some(ContentType::CSV));
+        let csv = response.into_string().await.unwrap();
+        assert_eq!(csv.lines().count(), 5);
+        assert!(csv.starts_with("id,manufacturer,model,slot,price_cents,currency"));
+
+        let response = client.get("/components/export?format=xml").dispatch().await;
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+    }
+
+    #[rocket::async_test]
+    async fn create_is_for_admins() {
+        let repository = InMemoryRepository::new();
+        let (_, user) = log_in(&repository, Role::User);
+        let (_, admin) = log_in(&repository, Role::Admin);
+        let client = client(&repository).await;
+        let component = json!({
+            "manufacturer": "Acme",
+            "model": "C1",
+            "slot": "cpu",
+            "price": { "amount": 100_000, "currency": "BRL" },
+        });
+
+        let response = client.post("/components").json(&component).dispatch().await;
+        assert_eq!(response.status(), Status::Unauthorized);
+
+        let response = client.post("/components").header(user).json(&component).dispatch().await;
+        assert_eq!(response.status(), Status::Forbidden);
+
+        let response = client.post("/components").header(admin.clone()).json(&component).dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+        let created = response.into_json::<Component>().await.unwrap();
+        assert_eq!(repository.get_component(created.id).await.unwrap().model, "C1");
+
+        let response = client.post("/components").header(admin.clone()).json(&component).dispatch().await;
+        assert_eq!(response.status(), Status::Conflict);
+
+        let mut unknown_slot = component.clone();
+        unknown_slot["slot"] = json!("psu");
+        let response = client.post("/components").header(admin.clone()).json(&unknown_slot).dispatch().await;
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+
+        let mut too_expensive = component.clone();
+        too_expensive["model"] = json!("C2");
+        too_expensive["price"]["amount"] = json!(i64::MAX);
+        let response = client.post("/components").header(admin).json(&too_expensive).dispatch().await;
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+    }
+
+    #[rocket::async_test]
+    async fn import_reports_every_row() {
+        let repository = InMemoryRepository::new();
+        let existing = add_component(&repository, "cpu", "C1", Money::new(100_000, "BRL")).await;
+        let (_, admin) = log_in(&repository, Role::Admin);
+        let client = client(&repository).await;
+        let catalog = "\
+manufacturer,model,slot,price_cents,currency,memory_generations,interfaces
+Acme,C1,cpu,90000,BRL,ddr4;ddr5,
+Acme,G1,gpu,200000,BRL,,pcie
+Acme,X1,psu,10000,BRL,,
+";
+
+        let response = client.post("/components/import?dry_run=true")
+            .header(admin.clone())
+            .header(ContentType::CSV)
+            .body(catalog)
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Ok);
+        let report = response.into_json::<ImportReport>().await.unwrap();
+        assert_eq!((report.created, report.updated, report.rejected), (1, 1, 1));
+        assert_eq!(repository.find_component_ids(&[("Acme".to_owned(), "G1".to_owned())]).await.unwrap().len(), 0);
+
+        let response = client.post("/components/import")
+            .header(admin.clone())
+            .header(ContentType::CSV)
+            .body(catalog)
+            .dispatch()
+            .await;
+        let report = response.into_json::<ImportReport>().await.unwrap();
+        let statuses: Vec<ImportStatus> = report.rows.iter().map(|row| row.status).collect();
+        assert_eq!(statuses, [ImportStatus::Updated, ImportStatus::Created, ImportStatus::Rejected]);
+        assert_eq!(report.rows[2].errors[0].field, "slot");
+        assert_eq!(repository.get_component(existing.id).await.unwrap().price, Money::new(90_000, "BRL"));
+        let created = report.rows[1].id.unwrap();
+        assert_eq!(repository.get_component(created).await.unwrap().model, "G1");
+        assert_eq!(repository.get_component(created).await.unwrap().interfaces, [Interface::Pcie]);
+        assert_eq!(repository.get_component(existing.id).await.unwrap().memory_generations, [MemoryGeneration::Ddr4, MemoryGeneration::Ddr5]);
+
+        let response = client.post("/components/import").header(admin).header(ContentType::XML).body("<catalog/>").dispatch().await;
+        assert_eq!(response.status(), Status::UnsupportedMediaType);
+    }
+
+    #[rocket::async_test]
+    async fn update_replaces_the_component() {
+        let repository = InMemoryRepository::new();
+        let cpu = add_component(&repository, "cpu", "C1", Money::new(100_000, "BRL")).await;
+        let (_, admin) = log_in(&repository, Role::Admin);
+        let client = client(&repository).await;
+
+        let response = client.put(format!("/components/{}", cpu.id))
+            .header(admin.clone())
+            .json(&json!({
+                "manufacturer": "Acme",
+                "model": "C1 Pro",
+                "slot": "cpu",
+                "price": { "amount": 120_000, "currency": "BRL" },
+                "socket": "AM5",
+            }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Ok);
+        let updated = repository.get_component(cpu.id).await.unwrap();
+        assert_eq!((updated.model.as_str(), updated.socket.as_deref()), ("C1 Pro", Some("AM5")));
+
+        let response = client.put(format!("/components/{}", uuid::Uuid::new_v4()))
+            .header(admin.clone())
+            .json(&json!({ "manufacturer": "Acme", "model": "C2", "slot": "cpu", "price": { "amount": 1, "currency": "BRL" } }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::NotFound);
+
+        let response = client.put(format!("/components/{}", cpu.id))
+            .header(admin)
+            .json(&json!({ "manufacturer": "Acme", "model": "C1", "slot": "cpu", "price": { "amount": -1, "currency": "BRL" } }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+    }
+
+    #[rocket::async_test]
+    async fn patch_only_changes_what_is_sent() {
+        let repository = InMemoryRepository::new();
+        let cpu = add_component(&repository, "cpu", "C1", Money::new(100_000, "BRL")).await;
+        let (_, admin) = log_in(&repository, Role::Admin);
+        let client = client(&repository).await;
+
+        let response = client.patch(format!("/components/{}", cpu.id))
+            .header(admin.clone())
+            .json(&json!({ "model": "C1 Pro" }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Ok);
+        let patched = response.into_json::<Component>().await.unwrap();
+        assert_eq!((patched.model.as_str(), patched.price), ("C1 Pro", Money::new(100_000, "BRL")));
+
+        let response = client.patch(format!("/components/{}", uuid::Uuid::new_v4()))
+            .header(admin)
+            .json(&json!({ "model": "C2" }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::NotFound);
+    }
+
+    #[rocket::async_test]
+    async fn delete_refuses_components_in_rigs() {
+        let repository = InMemoryRepository::new();
+        let parts = add_parts(&repository).await;
+        let spare = add_component(&repository, "gpu", "G2", Money::new(200_000, "BRL")).await;
+        let (_, admin) = log_in(&repository, Role::Admin);
+        let client = client(&repository).await;
+
+        let rig = CreateRigData {
+            name: "Rig".to_owned(),
+            components: parts.iter().map(|component| api_types::rig::RigComponentData { component_id: component.id, quantity: 1 }).collect(),
+            allow_incompatible: false,
+        };
+        let response = client.post("/rigs").header(admin.clone()).json(&rig).dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+
+        let response = client.delete(format!("/components/{}", parts[0].id)).header(admin.clone()).dispatch().await;
+        assert_eq!(response.status(), Status::Conflict);
+        let error = response.into_json::<Value>().await.unwrap();
+        assert_eq!(error["details"][0]["name"], "Rig");
+
+        let response = client.delete(format!("/components/{}", spare.id)).header(admin.clone()).dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+
+        let response = client.delete(format!("/components/{}", spare.id)).header(admin).dispatch().await;
+        assert_eq!(response.status(), Status::NotFound);
+    }
 }
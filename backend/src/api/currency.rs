@@ -0,0 +1,86 @@
+use api_types::currency::{ExchangeRate, UpdateExchangeRateData};
+use rocket::{fairing::AdHoc, get, put, routes, serde::json::Json, State};
+
+use crate::{
+    auth::{Editor, RequireRole},
+    error::{ApiError, ApiResult},
+    money::{Converter, BASE_CURRENCY},
+    repository::{ComponentRepository, CurrencyRepository},
+    validation::{self, ValidationErrors},
+};
+
+pub fn stage() -> AdHoc {
+    AdHoc::on_ignite("Currency Routes", |rocket| async {
+        rocket.mount("/exchange-rates", routes![list, detail, update])
+    })
+}
+
+/// Converter into `currency`, or the base currency if none was asked for.
+/// Unknown currencies fail validation under the `currency` field.
+pub async fn converter(currency: Option<&str>, components: &dyn ComponentRepository) -> ApiResult<Converter> {
+    let currency = currency.unwrap_or(BASE_CURRENCY);
+
+    match Converter::new(currency, components.list_rates().await?) {
+        Some(converter) => Ok(converter),
+        None => {
+            let mut errors = ValidationErrors::default();
+            errors.add("currency", format!("no exchange rate for {currency}"));
+            Err(errors.into())
+        },
+    }
+}
+
+#[utoipa::path(
+    context_path = "/exchange-rates",
+    tag = "exchange rates",
+    operation_id = "list_exchange_rates",
+    responses(
+        (status = 200, description = "Every exchange rate", body = Vec<ExchangeRate>),
+    ),
+)]
+#[get("/")]
+pub async fn list(rates: &State<Box<dyn CurrencyRepository>>) -> ApiResult<Json<Vec<ExchangeRate>>> {
+    Ok(Json(rates.list_rates().await?))
+}
+
+#[utoipa::path(
+    context_path = "/exchange-rates",
+    tag = "exchange rates",
+    operation_id = "get_exchange_rate",
+    params(
+        ("code" = String, Path, description = "ISO 4217 currency code"),
+    ),
+    responses(
+        (status = 200, description = "The exchange rate", body = ExchangeRate),
+        (status = 404, description = "No such currency", body = ApiError),
+    ),
+)]
+#[get("/<code>")]
+pub async fn detail(code: &str, rates: &State<Box<dyn CurrencyRepository>>) -> ApiResult<Json<ExchangeRate>> {
+    Ok(Json(rates.get_rate(code).await?))
+}
+
+#[utoipa::path(
+    context_path = "/exchange-rates",
+    tag = "exchange rates",
+    operation_id = "update_exchange_rate",
+    params(
+        ("code" = String, Path, description = "ISO 4217 currency code"),
+    ),
+    responses(
+        (status = 200, description = "The created or updated exchange rate", body = ExchangeRate),
+        (status = 401, description = "Missing or invalid bearer token", body = ApiError),
+        (status = 403, description = "Only editors may do it", body = ApiError),
+        (status = 422, description = "Malformed code, a rate that isn't positive, or a base currency rate other than 1000000", body = ApiError),
+    ),
+    security(("bearer_auth" = [])),
+)]
+#[put("/<code>", data = "<rate>")]
+pub async fn update(_editor: RequireRole<Editor>, code: &str, rate: Json<UpdateExchangeRateData>, rates: &State<Box<dyn CurrencyRepository>>) -> ApiResult<Json<ExchangeRate>> {
+    let mut errors = ValidationErrors::default();
+    validation::currency::validate_code(code, "currency", &mut errors);
+    validation::currency::validate_rate(code, rate.rate_micros, &mut errors);
+    errors.into_result()?;
+
+    Ok(Json(rates.upsert_rate(code, rate.rate_micros).await?))
+}
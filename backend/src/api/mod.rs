@@ -1,2 +1,9 @@
 pub mod component;
-pub mod rig;
\ No newline at end of file
+pub mod currency;
+pub mod revision;
+pub mod rig;
+pub mod slot;
+pub mod user;
+
+#[cfg(test)]
+mod testing;
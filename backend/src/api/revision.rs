@@ -0,0 +1,260 @@
+use api_types::{revision::{RestoreRevisionQuery, RevisionDiff, RigRevisionWithComponents}, rig::RigWithComponents};
+use rocket::{fairing::AdHoc, form, get, post, routes, serde::json::Json, State};
+use uuid::Uuid;
+
+use crate::{
+    api::{currency::converter, rig::check_against},
+    auth::AuthenticatedUser,
+    error::{ApiError, ApiResult},
+    repository::{ComponentRepository, RevisionRepository, RigRepository},
+    validation::ValidationErrors,
+};
+
+pub fn stage() -> AdHoc {
+    AdHoc::on_ignite("Rig Revision Routes", |rocket| async {
+        rocket.mount("/rigs", routes![list, detail, diff, restore])
+    })
+}
+
+#[utoipa::path(
+    context_path = "/rigs",
+    tag = "rig revisions",
+    operation_id = "list_rig_revisions",
+    params(
+        ("id" = Uuid, Path, description = "Rig id"),
+        ("currency" = Option<String>, Query, description = "Currency to convert prices to, BRL without it"),
+    ),
+    responses(
+        (status = 200, description = "Every revision of the rig, oldest first", body = Vec<RigRevisionWithComponents>),
+        (status = 400, description = "Malformed id", body = ApiError),
+        (status = 422, description = "Unknown `currency`", body = ApiError),
+    ),
+)]
+#[get("/<id>/revisions?<currency>")]
+pub async fn list(id: &str, currency: Option<&str>, components: &State<Box<dyn ComponentRepository>>, revisions: &State<Box<dyn RevisionRepository>>) -> ApiResult<Json<Vec<RigRevisionWithComponents>>> {
+    let id = Uuid::parse_str(id)?;
+    let converter = converter(currency, components.as_ref()).await?;
+
+    Ok(Json(revisions.list_revisions(id, &converter).await?))
+}
+
+#[utoipa::path(
+    context_path = "/rigs",
+    tag = "rig revisions",
+    operation_id = "get_rig_revision",
+    params(
+        ("id" = Uuid, Path, description = "Rig id"),
+        ("number" = i32, Path, description = "Revision number, from 1"),
+        ("currency" = Option<String>, Query, description = "Currency to convert prices to, BRL without it"),
+    ),
+    responses(
+        (status = 200, description = "The revision", body = RigRevisionWithComponents),
+        (status = 400, description = "Malformed id", body = ApiError),
+        (status = 404, description = "No such revision", body = ApiError),
+        (status = 422, description = "Unknown `currency`", body = ApiError),
+    ),
+)]
+#[get("/<id>/revisions/<number>?<currency>")]
+pub async fn detail(id: &str, number: i32, currency: Option<&str>, components: &State<Box<dyn ComponentRepository>>, revisions: &State<Box<dyn RevisionRepository>>) -> ApiResult<Json<RigRevisionWithComponents>> {
+    let id = Uuid::parse_str(id)?;
+    let converter = converter(currency, components.as_ref()).await?;
+
+    Ok(Json(revisions.get_revision(id, number, &converter).await?))
+}
+
+/// What changed from revision `from` to revision `to`, both priced as they
+/// were when taken
+#[utoipa::path(
+    context_path = "/rigs",
+    tag = "rig revisions",
+    operation_id = "diff_rig_revisions",
+    params(
+        ("id" = Uuid, Path, description = "Rig id"),
+        ("from" = i32, Path, description = "Revision compared from"),
+        ("to" = i32, Path, description = "Revision compared to"),
+        ("currency" = Option<String>, Query, description = "Currency to convert prices to, BRL without it"),
+    ),
+    responses(
+        (status = 200, description = "What changed between the revisions", body = RevisionDiff),
+        (status = 400, description = "Malformed id", body = ApiError),
+        (status = 404, description = "No such revision", body = ApiError),
+        (status = 422, description = "Unknown `currency`", body = ApiError),
+    ),
+)]
+#[get("/<id>/revisions/<from>/diff/<to>?<currency>")]
+pub async fn diff(id: &str, from: i32, to: i32, currency: Option<&str>, components: &State<Box<dyn ComponentRepository>>, revisions: &State<Box<dyn RevisionRepository>>) -> ApiResult<Json<RevisionDiff>> {
+    let id = Uuid::parse_str(id)?;
+    let converter = converter(currency, components.as_ref()).await?;
+
+    let from = revisions.get_revision(id, from, &converter).await?;
+    let to = revisions.get_revision(id, to, &converter).await?;
+
+    Ok(Json(RevisionDiff::new(from, to, converter.currency())))
+}
+
+/// Brings the rig back to the name and components of revision `number`,
+/// which becomes a new revision itself
+#[utoipa::path(
+    context_path = "/rigs",
+    tag = "rig revisions",
+    operation_id = "restore_rig_revision",
+    params(
+        ("id" = Uuid, Path, description = "Rig id"),
+        ("number" = i32, Path, description = "Revision number, from 1"),
+        RestoreRevisionQuery,
+    ),
+    responses(
+        (status = 200, description = "The rig, with the components it had in the revision", body = RigWithComponents),
+        (status = 400, description = "Malformed id", body = ApiError),
+        (status = 401, description = "Missing or invalid bearer token", body = ApiError),
+        (status = 403, description = "Not allowed to manage it", body = ApiError),
+        (status = 404, description = "No such revision", body = ApiError),
+        (status = 422, description = "Some of the components no longer exist or no longer fit their slots; they're incompatible without `allow_incompatible`; or an unknown currency", body = ApiError),
+    ),
+    security(("bearer_auth" = [])),
+)]
+#[post("/<id>/revisions/<number>/restore?<query..>")]
+pub async fn restore(
+    current: AuthenticatedUser,
+    id: &str,
+    number: i32,
+    query: Result<RestoreRevisionQuery, form::Errors<'_>>,
+    components: &State<Box<dyn ComponentRepository>>,
+    rigs: &State<Box<dyn RigRepository>>,
+    revisions: &State<Box<dyn RevisionRepository>>,
+) -> ApiResult<Json<RigWithComponents>> {
+    let id = Uuid::parse_str(id)?;
+    let RestoreRevisionQuery { currency, allow_incompatible } = query.map_err(ValidationErrors::from)?;
+    current.ensure_can_manage(rigs.get_rig_owner(id).await?)?;
+
+    let converter = converter(currency.as_deref(), components.as_ref()).await?;
+
+    revisions.restore_revision(id, number, Box::new(move |restored, catalog| {
+        check_against(restored, allow_incompatible, ValidationErrors::default(), catalog)
+    })).await?;
+
+    Ok(Json(rigs.get_rig(id, &converter).await?))
+}
+
+#[cfg(test)]
+mod tests {
+    use api_types::{
+        error::ApiError,
+        money::Money,
+        revision::{RevisionDiff, RigRevisionWithComponents},
+        rig::{CreateRigData, RigWithComponents},
+        types::Role,
+    };
+    use rocket::{http::Status, serde::json::serde_json::json};
+
+    use crate::{
+        api::testing::{add_component, add_mismatched_memory, add_parts, client, create_rig, log_in, rig_data},
+        repository::{memory::InMemoryRepository, ComponentRepository},
+    };
+
+    #[rocket::async_test]
+    async fn every_change_is_a_revision_to_go_back_to() {
+        let repository = InMemoryRepository::new();
+        let parts = add_parts(&repository).await;
+        let gpu = add_component(&repository, "gpu", "G2", Money::new(300_000, "BRL")).await;
+        let (_, owner) = log_in(&repository, Role::User);
+        let (_, stranger) = log_in(&repository, Role::User);
+        let client = client(&repository).await;
+        let rig = create_rig(&client, &owner, &rig_data("Rig", &parts)).await;
+
+        let response = client.patch(format!("/rigs/{}", rig.id)).header(owner.clone()).json(&json!({ "name": "Renamed" })).dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+        let components = rig_data("Renamed", &[parts[0].clone(), gpu.clone(), parts[2].clone(), parts[3].clone()]).components;
+        let response = client.put(format!("/rigs/{}/components", rig.id))
+            .header(owner.clone())
+            .json(&json!({ "components": components }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Ok);
+
+        let response = client.get(format!("/rigs/{}/revisions", rig.id)).dispatch().await;
+        let revisions = response.into_json::<Vec<RigRevisionWithComponents>>().await.unwrap();
+        let names: Vec<(i32, &str)> = revisions.iter().map(|revision| (revision.number, revision.name.as_str())).collect();
+        assert_eq!(names, [(1, "Rig"), (2, "Renamed"), (3, "Renamed")]);
+        assert_eq!(revisions[0].total_price, Money::new(370_000, "BRL"));
+
+        let response = client.get(format!("/rigs/{}/revisions/1/diff/3?currency=USD", rig.id)).dispatch().await;
+        let diff = response.into_json::<RevisionDiff>().await.unwrap();
+        assert_eq!(diff.slots.len(), 1);
+        assert_eq!(diff.slots[0].swapped[0].to.component.component_id, gpu.id);
+        assert_eq!(diff.price_delta.currency, "USD");
+
+        let response = client.post(format!("/rigs/{}/revisions/1/restore", rig.id)).header(stranger).dispatch().await;
+        assert_eq!(response.status(), Status::Forbidden);
+        let response = client.post(format!("/rigs/{}/revisions/9/restore", rig.id)).header(owner.clone()).dispatch().await;
+        assert_eq!(response.status(), Status::NotFound);
+
+        let response = client.post(format!("/rigs/{}/revisions/1/restore", rig.id)).header(owner).dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+        let restored = response.into_json::<RigWithComponents>().await.unwrap();
+        assert_eq!(restored.name, "Rig");
+        assert!(restored.components.iter().any(|item| item.component.id == parts[1].id));
+
+        let response = client.get(format!("/rigs/{}/revisions/4", rig.id)).dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.into_json::<RigRevisionWithComponents>().await.unwrap().name, "Rig");
+    }
+
+    #[rocket::async_test]
+    async fn restoring_checks_the_catalog_as_it_is_now() {
+        let repository = InMemoryRepository::new();
+        let parts = add_parts(&repository).await;
+        let gpu = add_component(&repository, "gpu", "G2", Money::new(300_000, "BRL")).await;
+        let (_, owner) = log_in(&repository, Role::User);
+        let client = client(&repository).await;
+        let rig = create_rig(&client, &owner, &rig_data("Rig", &parts)).await;
+
+        let components = rig_data("Rig", &[parts[0].clone(), gpu, parts[2].clone(), parts[3].clone()]).components;
+        let response = client.put(format!("/rigs/{}/components", rig.id))
+            .header(owner.clone())
+            .json(&json!({ "components": components }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Ok);
+        assert!(repository.delete_component(parts[1].id).await.unwrap().is_empty());
+
+        let response = client.post(format!("/rigs/{}/revisions/1/restore", rig.id)).header(owner).dispatch().await;
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+        let error = response.into_json::<ApiError>().await.unwrap();
+        assert!(error.fields.iter().any(|field| field.message.contains(&parts[1].id.to_string())));
+
+        let response = client.get(format!("/rigs/{}/revisions", rig.id)).dispatch().await;
+        assert_eq!(response.into_json::<Vec<RigRevisionWithComponents>>().await.unwrap().len(), 2);
+    }
+
+    #[rocket::async_test]
+    async fn restoring_checks_compatibility_unless_allowed_not_to() {
+        let repository = InMemoryRepository::new();
+        let parts = add_parts(&repository).await;
+        let (cpu, memory) = add_mismatched_memory(&repository).await;
+        let (_, owner) = log_in(&repository, Role::User);
+        let client = client(&repository).await;
+        let mismatched = [cpu, parts[1].clone(), memory, parts[3].clone()];
+        let rig = create_rig(&client, &owner, &CreateRigData { allow_incompatible: true, ..rig_data("Rig", &mismatched) }).await;
+
+        let response = client.put(format!("/rigs/{}/components", rig.id))
+            .header(owner.clone())
+            .json(&json!({ "components": rig_data("Rig", &parts).components }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Ok);
+
+        let response = client.post(format!("/rigs/{}/revisions/1/restore", rig.id)).header(owner.clone()).dispatch().await;
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+        let error = response.into_json::<ApiError>().await.unwrap();
+        assert_eq!(error.code, "incompatible_components");
+
+        let response = client.post(format!("/rigs/{}/revisions/1/restore?allow_incompatible=maybe", rig.id)).header(owner.clone()).dispatch().await;
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+
+        let response = client.post(format!("/rigs/{}/revisions/1/restore?allow_incompatible=true", rig.id)).header(owner).dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+        let restored = response.into_json::<RigWithComponents>().await.unwrap();
+        assert!(restored.components.iter().any(|item| item.component.id == mismatched[2].id));
+    }
+}
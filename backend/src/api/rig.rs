@@ -1,33 +1,897 @@
-use rocket::{fairing::AdHoc, get, post, routes, serde::json::Json, Responder};
-use rocket_db_pools::{Connection, diesel::QueryResult};
+use std::collections::HashMap;
+
+use api_types::{
+    compatibility::CompatibilityReport,
+    component::Component,
+    export::ExportFormat,
+    rig::{CloneRigData, CreateRigData, RenameRigData, Rig, RigComponentData, RigComponentItem, RigValuation, RigWithComponents, ShareRigData, UpdateRigComponentsData},
+};
+use rocket::{
+    delete, fairing::AdHoc, form,
+    futures::StreamExt,
+    get,
+    http::ContentType,
+    patch, post, put,
+    response::stream::TextStream,
+    routes,
+    serde::json::Json,
+    State,
+};
+use time::{Date, OffsetDateTime};
 use uuid::Uuid;
 
-use crate::{database::Db, dto::rig::{CreateRigData, RigWithComponents}, repository::{component, rig}};
+use crate::{
+    api::currency::converter,
+    auth::AuthenticatedUser,
+    compatibility,
+    dto::rig::{merge_clone_components, RigComponentRow},
+    error::{ApiError, ApiResult},
+    export::{opened, Encoder, ExportFormatExt},
+    models::rig::RigComponent,
+    money::Convert,
+    repository::{rig::{ComponentsCheck, Fork}, ComponentRepository, RigRepository},
+    validation::{self, rig::Catalog, ValidationErrors},
+};
 
 pub fn stage() -> AdHoc {
     AdHoc::on_ignite("Rig Routes", |rocket| async {
-        rocket.mount("/rigs", routes![list, create])
+        rocket
+            .mount("/rigs", routes![list, export, detail, valuation, create, check_compatibility, rename, update_components, clone, share, delete])
+            .mount("/shared", routes![shared])
     })
 }
 
-#[get("/")]
-pub async fn list(mut db: Connection<Db>) -> QueryResult<Json<Vec<RigWithComponents>>> {
-    Ok(Json(rig::list_rigs(&mut db).await?))
+fn component_ids(components: &[RigComponentData]) -> Vec<Uuid> {
+    components
+        .iter()
+        .map(|component| component.component_id)
+        .collect()
+}
+
+/// Share links are the owner's and admins' to hand out, so everyone else
+/// gets rigs without them
+fn hide_share_slug(share_slug: &mut Option<String>, owner_id: Option<Uuid>, viewer: Option<&AuthenticatedUser>) {
+    if !viewer.is_some_and(|viewer| viewer.can_manage(owner_id)) {
+        *share_slug = None;
+    }
+}
+
+/// Checks `components` against the catalog read in the transaction that
+/// writes them, failing with the `errors` found up front along with theirs
+fn components_check(components: Vec<RigComponentData>, allow_incompatible: bool, errors: ValidationErrors) -> ComponentsCheck {
+    Box::new(move |catalog| check_against(&components, allow_incompatible, errors, catalog))
+}
+
+/// Fails with what's wrong with `components` in `catalog` along with
+/// `errors`, then, unless `allow_incompatible`, with their incompatibilities
+pub(super) fn check_against(components: &[RigComponentData], allow_incompatible: bool, mut errors: ValidationErrors, catalog: &Catalog) -> ApiResult<()> {
+    validation::rig::check_components(components, &mut errors, catalog);
+    errors.into_result()?;
+
+    if !allow_incompatible {
+        check_compatible(&catalog.components_of(&component_ids(components)))?;
+    }
+
+    Ok(())
+}
+
+/// Fails with the incompatibilities among `components`, if any
+fn check_compatible(components: &[Component]) -> ApiResult<()> {
+    let report = compatibility::evaluate(components);
+
+    if report.compatible {
+        Ok(())
+    } else {
+        Err(report.into())
+    }
+}
+
+/// `mine=true` only lists the authenticated user's rigs. Share slugs are
+/// only listed for the rigs the caller can manage.
+#[utoipa::path(
+    context_path = "/rigs",
+    tag = "rigs",
+    operation_id = "list_rigs",
+    params(
+        ("currency" = Option<String>, Query, description = "Currency to convert prices to, BRL without it"),
+        ("mine" = Option<bool>, Query, description = "Only list the authenticated user's rigs"),
+    ),
+    responses(
+        (status = 200, description = "Every rig", body = Vec<RigWithComponents>),
+        (status = 401, description = "Missing or invalid bearer token with `mine=true`", body = ApiError),
+        (status = 422, description = "Unknown `currency`", body = ApiError),
+    ),
+    security((), ("bearer_auth" = [])),
+)]
+#[get("/?<currency>&<mine>")]
+pub async fn list(
+    currency: Option<&str>,
+    mine: Option<bool>,
+    current: Result<AuthenticatedUser, ApiError>,
+    components: &State<Box<dyn ComponentRepository>>,
+    rigs: &State<Box<dyn RigRepository>>,
+) -> ApiResult<Json<Vec<RigWithComponents>>> {
+    let viewer = match mine {
+        Some(true) => Some(current?),
+        _ => current.ok(),
+    };
+    let owner_id = match mine {
+        Some(true) => viewer.as_ref().map(|viewer| viewer.user.id),
+        _ => None,
+    };
+    let converter = converter(currency, components.as_ref()).await?;
+
+    let mut found = rigs.list_rigs(owner_id, &converter).await?;
+    for rig in &mut found {
+        hide_share_slug(&mut rig.share_slug, rig.owner_id, viewer.as_ref());
+    }
+
+    Ok(Json(found))
+}
+
+/// Every rig, streamed as it's read: one object per rig in JSON and NDJSON,
+/// one line per rig component in CSV. Share slugs are only exported for the
+/// rigs the caller can manage.
+#[utoipa::path(
+    context_path = "/rigs",
+    tag = "rigs",
+    operation_id = "export_rigs",
+    params(
+        ("format" = Option<ExportFormat>, Query, description = "JSON by default"),
+        ("currency" = Option<String>, Query, description = "Currency to convert prices to, BRL without it"),
+        ("mine" = Option<bool>, Query, description = "Only export the authenticated user's rigs"),
+    ),
+    responses(
+        (status = 200, description = "Every rig, streamed: one object per rig in JSON and NDJSON, one line per rig component in CSV. Cut short by an error, JSON is left unclosed, NDJSON ends with an `export_failed` error line and CSV with a `# export_failed` line", content((String = "text/csv"), (Vec<RigWithComponents> = "application/json"), (String = "application/x-ndjson"))),
+        (status = 401, description = "Missing or invalid bearer token with `mine=true`", body = ApiError),
+        (status = 422, description = "Unknown `format` or `currency`", body = ApiError),
+        (status = 500, description = "The export failed before its first record", body = ApiError),
+    ),
+    security((), ("bearer_auth" = [])),
+)]
+#[get("/export?<format>&<currency>&<mine>")]
+pub async fn export(
+    format: form::Result<'_, ExportFormat>,
+    currency: Option<&str>,
+    mine: Option<bool>,
+    current: Result<AuthenticatedUser, ApiError>,
+    components: &State<Box<dyn ComponentRepository>>,
+    rigs: &State<Box<dyn RigRepository>>,
+) -> ApiResult<(ContentType, TextStream![String])> {
+    let format = ExportFormat::from_query(format)?;
+    let viewer = match mine {
+        Some(true) => Some(current?),
+        _ => current.ok(),
+    };
+    let owner_id = match mine {
+        Some(true) => viewer.as_ref().map(|viewer| viewer.user.id),
+        _ => None,
+    };
+    let converter = converter(currency, components.as_ref()).await?;
+    let mut rows = opened(rigs.stream_rig_components(owner_id).await?).await?;
+
+    let mut encoder = Encoder::new(format);
+    let opening = encoder.open::<RigComponentRow>().map_err(|err| {
+        rocket::error!("could not export rigs: {err}");
+        ApiError::internal()
+    })?;
+    let body = TextStream! {
+        yield opening;
+
+        // Rig whose components are being gathered, for JSON
+        let mut pending: Option<(Rig, Vec<RigComponentItem>)> = None;
+
+        while let Some(row) = rows.next().await {
+            let (mut rig, item) = match row {
+                Ok((rig, component)) => (rig, component.map(|(rig_component, component)| {
+                    Ok::<_, ApiError>(RigComponentItem::new(component.converted(&converter)?, rig_component.quantity))
+                })),
+                Err(err) => {
+                    rocket::error!("could not export rigs: {err:?}");
+                    yield encoder.fail();
+                    return;
+                },
+            };
+            let item = match item.transpose() {
+                Ok(item) => item,
+                Err(err) => {
+                    rocket::error!("could not export rigs: {err:?}");
+                    yield encoder.fail();
+                    return;
+                },
+            };
+            hide_share_slug(&mut rig.share_slug, rig.owner_id, viewer.as_ref());
+
+            let piece = match encoder.format() {
+                ExportFormat::Csv => match item {
+                    Some(item) => encoder.csv_record(&RigComponentRow::new(&rig, &item)).map_err(|err| err.to_string()),
+                    None => continue,
+                },
+                ExportFormat::Json | ExportFormat::Ndjson => {
+                    if let Some((_, items)) = pending.as_mut().filter(|(pending, _)| pending.id == rig.id) {
+                        items.extend(item);
+                        continue;
+                    }
+
+                    match pending.replace((rig, item.into_iter().collect())) {
+                        Some((done, items)) => encoder.json_record(&done.with_components(items, converter.currency())).map_err(|err| err.to_string()),
+                        None => continue,
+                    }
+                },
+            };
+
+            match piece {
+                Ok(piece) => yield piece,
+                Err(err) => {
+                    rocket::error!("could not export rigs: {err}");
+                    yield encoder.fail();
+                    return;
+                },
+            }
+        }
+
+        if let Some((done, items)) = pending.take() {
+            match encoder.json_record(&done.with_components(items, converter.currency())) {
+                Ok(piece) => yield piece,
+                Err(err) => {
+                    rocket::error!("could not export rigs: {err}");
+                    yield encoder.fail();
+                    return;
+                },
+            }
+        }
+
+        yield encoder.close();
+    };
+
+    Ok((format.content_type(), body))
+}
+
+#[utoipa::path(
+    context_path = "/rigs",
+    tag = "rigs",
+    operation_id = "get_rig",
+    params(
+        ("id" = Uuid, Path, description = "Rig id"),
+        ("currency" = Option<String>, Query, description = "Currency to convert prices to, BRL without it"),
+    ),
+    responses(
+        (status = 200, description = "The rig, with its share slug if the caller can manage it", body = RigWithComponents),
+        (status = 400, description = "Malformed id", body = ApiError),
+        (status = 404, description = "No such rig", body = ApiError),
+        (status = 422, description = "Unknown `currency`", body = ApiError),
+    ),
+    security((), ("bearer_auth" = [])),
+)]
+#[get("/<id>?<currency>")]
+pub async fn detail(current: Option<AuthenticatedUser>, id: &str, currency: Option<&str>, components: &State<Box<dyn ComponentRepository>>, rigs: &State<Box<dyn RigRepository>>) -> ApiResult<Json<RigWithComponents>> {
+    let id = Uuid::parse_str(id)?;
+    let converter = converter(currency, components.as_ref()).await?;
+
+    let mut rig = rigs.get_rig(id, &converter).await?;
+    hide_share_slug(&mut rig.share_slug, rig.owner_id, current.as_ref());
+
+    Ok(Json(rig))
+}
+
+/// What the rig would have cost at the end of `at` (today if not given)
+#[utoipa::path(
+    context_path = "/rigs",
+    tag = "rigs",
+    operation_id = "get_rig_valuation",
+    params(
+        ("id" = Uuid, Path, description = "Rig id"),
+        ("at" = Option<Date>, Query, description = "Day the rig is valued at, today by default"),
+        ("currency" = Option<String>, Query, description = "Currency to convert prices to, BRL without it"),
+    ),
+    responses(
+        (status = 200, description = "What the rig cost at the end of the day", body = RigValuation),
+        (status = 400, description = "Malformed id", body = ApiError),
+        (status = 404, description = "No such rig", body = ApiError),
+        (status = 422, description = "Malformed `at` date or unknown `currency`", body = ApiError),
+    ),
+)]
+#[get("/<id>/valuation?<at>&<currency>")]
+pub async fn valuation(id: &str, at: form::Result<'_, Date>, currency: Option<&str>, components: &State<Box<dyn ComponentRepository>>, rigs: &State<Box<dyn RigRepository>>) -> ApiResult<Json<RigValuation>> {
+    let id = Uuid::parse_str(id)?;
+    let at = match at {
+        Ok(at) => at,
+        Err(errors) if errors.iter().all(|error| matches!(error.kind, form::error::ErrorKind::Missing)) => {
+            OffsetDateTime::now_utc().date()
+        },
+        Err(errors) => return Err(ValidationErrors::from(errors).into()),
+    };
+
+    let converter = converter(currency, components.as_ref()).await?;
+
+    Ok(Json(rigs.get_rig_valuation(id, at, &converter).await?))
+}
+
+#[utoipa::path(
+    context_path = "/rigs",
+    tag = "rigs",
+    operation_id = "create_rig",
+    params(
+        ("currency" = Option<String>, Query, description = "Currency to convert prices to, BRL without it"),
+    ),
+    responses(
+        (status = 200, description = "The created rig", body = RigWithComponents),
+        (status = 401, description = "Missing or invalid bearer token", body = ApiError),
+        (status = 422, description = "Empty name; unknown or repeated components, quantities outside 1 to 1000, prices adding up past what fits or slots over or under their bounds; incompatible components without `allow_incompatible`; or an unknown `currency`", body = ApiError),
+    ),
+    security(("bearer_auth" = [])),
+)]
+#[post("/?<currency>", data = "<rig>")]
+pub async fn create(current: AuthenticatedUser, rig: Json<CreateRigData>, currency: Option<&str>, components: &State<Box<dyn ComponentRepository>>, rigs: &State<Box<dyn RigRepository>>) -> ApiResult<Json<RigWithComponents>> {
+    let mut errors = ValidationErrors::default();
+    validation::rig::validate_name(&rig.name, &mut errors);
+
+    let CreateRigData { name, components: data, allow_incompatible } = rig.0;
+    let check = components_check(data.clone(), allow_incompatible, errors);
+    let rig = Rig::new(name, current.user.id);
+    let id = rig.id;
+    let rig_components = RigComponent::for_rig(id, data);
+    let converter = converter(currency, components.as_ref()).await?;
+
+    rigs.create_rig(rig, rig_components, check).await?;
+
+    Ok(Json(rigs.get_rig(id, &converter).await?))
+}
+
+#[utoipa::path(
+    context_path = "/rigs",
+    tag = "rigs",
+    operation_id = "check_rig_compatibility",
+    responses(
+        (status = 200, description = "Whether the components work together", body = CompatibilityReport),
+    ),
+)]
+#[post("/compatibility", data = "<rig>")]
+pub async fn check_compatibility(rig: Json<CreateRigData>, components: &State<Box<dyn ComponentRepository>>) -> ApiResult<Json<CompatibilityReport>> {
+    let found = components.list_components_by_id(&component_ids(&rig.components)).await?;
+
+    Ok(Json(compatibility::evaluate(&found)))
+}
+
+#[utoipa::path(
+    context_path = "/rigs",
+    tag = "rigs",
+    operation_id = "rename_rig",
+    params(
+        ("id" = Uuid, Path, description = "Rig id"),
+    ),
+    responses(
+        (status = 200, description = "The renamed rig", body = Rig),
+        (status = 400, description = "Malformed id", body = ApiError),
+        (status = 401, description = "Missing or invalid bearer token", body = ApiError),
+        (status = 403, description = "Not allowed to manage it", body = ApiError),
+        (status = 404, description = "No such rig", body = ApiError),
+        (status = 422, description = "Empty name", body = ApiError),
+    ),
+    security(("bearer_auth" = [])),
+)]
+#[patch("/<id>", data = "<rig>")]
+pub async fn rename(current: AuthenticatedUser, id: &str, rig: Json<RenameRigData>, rigs: &State<Box<dyn RigRepository>>) -> ApiResult<Json<Rig>> {
+    let id = Uuid::parse_str(id)?;
+    current.ensure_can_manage(rigs.get_rig_owner(id).await?)?;
+
+    let mut errors = ValidationErrors::default();
+    validation::rig::validate_name(&rig.name, &mut errors);
+    errors.into_result()?;
+
+    Ok(Json(rigs.rename_rig(id, &rig.name).await?))
+}
+
+#[utoipa::path(
+    context_path = "/rigs",
+    tag = "rigs",
+    operation_id = "update_rig_components",
+    params(
+        ("id" = Uuid, Path, description = "Rig id"),
+        ("currency" = Option<String>, Query, description = "Currency to convert prices to, BRL without it"),
+    ),
+    responses(
+        (status = 200, description = "The rig with its new components", body = RigWithComponents),
+        (status = 400, description = "Malformed id", body = ApiError),
+        (status = 401, description = "Missing or invalid bearer token", body = ApiError),
+        (status = 403, description = "Not allowed to manage it", body = ApiError),
+        (status = 404, description = "No such rig", body = ApiError),
+        (status = 422, description = "Unknown or repeated components, quantities outside 1 to 1000, prices adding up past what fits or slots over or under their bounds; incompatible components without `allow_incompatible`; or an unknown `currency`", body = ApiError),
+    ),
+    security(("bearer_auth" = [])),
+)]
+#[put("/<id>/components?<currency>", data = "<update>")]
+pub async fn update_components(current: AuthenticatedUser, id: &str, update: Json<UpdateRigComponentsData>, currency: Option<&str>, components: &State<Box<dyn ComponentRepository>>, rigs: &State<Box<dyn RigRepository>>) -> ApiResult<Json<RigWithComponents>> {
+    let id = Uuid::parse_str(id)?;
+    current.ensure_can_manage(rigs.get_rig_owner(id).await?)?;
+
+    let UpdateRigComponentsData { components: data, allow_incompatible } = update.0;
+    let check = components_check(data.clone(), allow_incompatible, ValidationErrors::default());
+    let rig_components = RigComponent::for_rig(id, data);
+    let converter = converter(currency, components.as_ref()).await?;
+
+    rigs.replace_rig_components(id, rig_components, check).await?;
+
+    Ok(Json(rigs.get_rig(id, &converter).await?))
+}
+
+/// Copies a rig and its components into a new rig owned by the caller, with
+/// `components` swapped in for the original's in their slots
+#[utoipa::path(
+    context_path = "/rigs",
+    tag = "rigs",
+    operation_id = "clone_rig",
+    params(
+        ("id" = Uuid, Path, description = "Rig id"),
+        ("currency" = Option<String>, Query, description = "Currency to convert prices to, BRL without it"),
+    ),
+    responses(
+        (status = 200, description = "The new rig, owned by the authenticated user", body = RigWithComponents),
+        (status = 400, description = "Malformed id", body = ApiError),
+        (status = 401, description = "Missing or invalid bearer token", body = ApiError),
+        (status = 404, description = "No such rig", body = ApiError),
+        (status = 422, description = "Empty name; the merged components are unknown or repeated, have quantities outside 1 to 1000, prices adding up past what fits or leave slots over or under their bounds; incompatible components without `allow_incompatible`; or an unknown `currency`", body = ApiError),
+    ),
+    security(("bearer_auth" = [])),
+)]
+#[post("/<id>/clone?<currency>", data = "<rig>")]
+pub async fn clone(current: AuthenticatedUser, id: &str, rig: Json<CloneRigData>, currency: Option<&str>, components: &State<Box<dyn ComponentRepository>>, rigs: &State<Box<dyn RigRepository>>) -> ApiResult<Json<RigWithComponents>> {
+    let id = Uuid::parse_str(id)?;
+    let owner_id = current.user.id;
+    let converter = converter(currency, components.as_ref()).await?;
+    let override_ids = component_ids(&rig.components);
+
+    let fork: Fork = Box::new(move |original, original_components, catalog| {
+        let override_slots: HashMap<Uuid, String> = catalog
+            .components_of(&component_ids(&rig.components))
+            .into_iter()
+            .map(|component| (component.id, component.slot))
+            .collect();
+        let merged = merge_clone_components(&rig, original_components, &override_slots);
+
+        let mut errors = ValidationErrors::default();
+        validation::rig::validate_name(&rig.name, &mut errors);
+        validation::rig::check_components(&merged, &mut errors, catalog);
+        errors.into_result()?;
+
+        if !rig.allow_incompatible {
+            check_compatible(&catalog.components_of(&component_ids(&merged)))?;
+        }
+
+        let clone = original.fork(rig.0.name, owner_id);
+        let rig_components = RigComponent::for_rig(clone.id, merged);
+        Ok((clone, rig_components))
+    });
+    let clone = rigs.clone_rig(id, &override_ids, fork).await?;
+
+    Ok(Json(rigs.get_rig(clone.id, &converter).await?))
+}
+
+/// Generates the rig's public link, or revokes it
+#[utoipa::path(
+    context_path = "/rigs",
+    tag = "rigs",
+    operation_id = "share_rig",
+    params(
+        ("id" = Uuid, Path, description = "Rig id"),
+    ),
+    responses(
+        (status = 200, description = "The rig, with its share slug when shared", body = Rig),
+        (status = 400, description = "Malformed id", body = ApiError),
+        (status = 401, description = "Missing or invalid bearer token", body = ApiError),
+        (status = 403, description = "Not allowed to manage it", body = ApiError),
+        (status = 404, description = "No such rig", body = ApiError),
+    ),
+    security(("bearer_auth" = [])),
+)]
+#[post("/<id>/share", data = "<share>")]
+pub async fn share(current: AuthenticatedUser, id: &str, share: Json<ShareRigData>, rigs: &State<Box<dyn RigRepository>>) -> ApiResult<Json<Rig>> {
+    let id = Uuid::parse_str(id)?;
+    current.ensure_can_manage(rigs.get_rig_owner(id).await?)?;
+
+    Ok(Json(rigs.share_rig(id, share.shared).await?))
+}
+
+/// Read-only view of a rig through its public link, no account needed
+#[utoipa::path(
+    context_path = "/shared",
+    tag = "rigs",
+    operation_id = "get_shared_rig",
+    params(
+        ("slug" = String, Path, description = "Share slug of the rig"),
+        ("currency" = Option<String>, Query, description = "Currency to convert prices to, BRL without it"),
+    ),
+    responses(
+        (status = 200, description = "The shared rig", body = RigWithComponents),
+        (status = 404, description = "No rig is shared under the slug", body = ApiError),
+        (status = 422, description = "Unknown `currency`", body = ApiError),
+    ),
+)]
+#[get("/<slug>?<currency>")]
+pub async fn shared(slug: &str, currency: Option<&str>, components: &State<Box<dyn ComponentRepository>>, rigs: &State<Box<dyn RigRepository>>) -> ApiResult<Json<RigWithComponents>> {
+    let converter = converter(currency, components.as_ref()).await?;
+
+    Ok(Json(rigs.get_shared_rig(slug, &converter).await?))
 }
 
-#[post("/", data = "<rig>")]
-pub async fn create(rig: Json<CreateRigData>, mut db: Connection<Db>) -> QueryResult<Json<RigWithComponents>> {
-    let (rig, rig_components) = rig.0.into();
-    rig::create_rig(&rig, &mut db).await?;
-    rig::create_rig_components(&rig_components, &mut db).await?;
+#[utoipa::path(
+    context_path = "/rigs",
+    tag = "rigs",
+    operation_id = "delete_rig",
+    params(
+        ("id" = Uuid, Path, description = "Rig id"),
+    ),
+    responses(
+        (status = 200, description = "The rig was deleted"),
+        (status = 400, description = "Malformed id", body = ApiError),
+        (status = 401, description = "Missing or invalid bearer token", body = ApiError),
+        (status = 403, description = "Not allowed to manage it", body = ApiError),
+        (status = 404, description = "No such rig", body = ApiError),
+    ),
+    security(("bearer_auth" = [])),
+)]
+#[delete("/<id>")]
+pub async fn delete(current: AuthenticatedUser, id: &str, rigs: &State<Box<dyn RigRepository>>) -> ApiResult<()> {
+    let id = Uuid::parse_str(id)?;
+    current.ensure_can_manage(rigs.get_rig_owner(id).await?)?;
+
+    match rigs.delete_rig(id).await? {
+        0 => Err(ApiError::not_found("rig not found")),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use api_types::{
+        compatibility::CompatibilityReport,
+        error::ApiError,
+        money::Money,
+        rig::{CreateRigData, Rig, RigValuation, RigWithComponents},
+        types::Role,
+    };
+    use rocket::{
+        http::{ContentType, Status},
+        serde::json::serde_json::json,
+    };
+    use uuid::Uuid;
+
+    use crate::{
+        api::testing::{add_component, add_mismatched_memory, add_parts, client, create_rig, log_in, rig_data},
+        repository::memory::InMemoryRepository,
+    };
+
+    #[rocket::async_test]
+    async fn list_only_shows_the_callers_rigs_when_asked() {
+        let repository = InMemoryRepository::new();
+        let parts = add_parts(&repository).await;
+        let (_, alice) = log_in(&repository, Role::User);
+        let (_, bob) = log_in(&repository, Role::User);
+        let client = client(&repository).await;
+        create_rig(&client, &alice, &rig_data("Alice's", &parts)).await;
+        create_rig(&client, &bob, &rig_data("Bob's", &parts)).await;
+
+        let response = client.get("/rigs?currency=USD").dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+        let rigs = response.into_json::<Vec<RigWithComponents>>().await.unwrap();
+        assert_eq!(rigs.len(), 2);
+        assert!(rigs.iter().all(|rig| rig.total_price.currency == "USD"));
+
+        let response = client.get("/rigs?mine=true").header(alice).dispatch().await;
+        let rigs = response.into_json::<Vec<RigWithComponents>>().await.unwrap();
+        let names: Vec<&str> = rigs.iter().map(|rig| rig.name.as_str()).collect();
+        assert_eq!(names, ["Alice's"]);
+
+        let response = client.get("/rigs?mine=true").dispatch().await;
+        assert_eq!(response.status(), Status::Unauthorized);
+    }
+
+    #[rocket::async_test]
+    async fn export_streams_every_rig() {
+        let repository = InMemoryRepository::new();
+        let parts = add_parts(&repository).await;
+        let (_, user) = log_in(&repository, Role::User);
+        let client = client(&repository).await;
+
+        // Without rigs, the CSV still has its header
+        let response = client.get("/rigs/export?format=csv").dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(
+            response.into_string().await.unwrap(),
+            "rig_id,rig_name,slot,component_id,manufacturer,model,quantity,price_cents,line_total_cents,currency\n",
+        );
+
+        create_rig(&client, &user, &rig_data("First", &parts)).await;
+        create_rig(&client, &user, &rig_data("Second", &parts)).await;
+
+        let response = client.get("/rigs/export?format=ndjson").dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+        let ndjson = response.into_string().await.unwrap();
+        let mut names: Vec<String> = ndjson
+            .lines()
+            .map(|line| rocket::serde::json::from_str::<RigWithComponents>(line).unwrap().name)
+            .collect();
+        names.sort();
+        assert_eq!(names, ["First", "Second"]);
+
+        // One line per rig component, after the header
+        let response = client.get("/rigs/export?format=csv").dispatch().await;
+        assert_eq!(response.content_type(), Some(ContentType::CSV));
+        assert_eq!(response.into_string().await.unwrap().lines().count(), 9);
+    }
+
+    #[rocket::async_test]
+    async fn detail_prices_every_component() {
+        let repository = InMemoryRepository::new();
+        let parts = add_parts(&repository).await;
+        let (_, user) = log_in(&repository, Role::User);
+        let client = client(&repository).await;
+        let rig = create_rig(&client, &user, &rig_data("Rig", &parts)).await;
+
+        let response = client.get(format!("/rigs/{}", rig.id)).dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+        let detail = response.into_json::<RigWithComponents>().await.unwrap();
+        assert_eq!(detail.components.len(), 4);
+        assert_eq!(detail.total_price, Money::new(370_000, "BRL"));
+
+        let response = client.get(format!("/rigs/{}", Uuid::new_v4())).dispatch().await;
+        assert_eq!(response.status(), Status::NotFound);
+    }
+
+    #[rocket::async_test]
+    async fn valuation_uses_the_prices_at_the_date() {
+        let repository = InMemoryRepository::new();
+        let parts = add_parts(&repository).await;
+        let (_, user) = log_in(&repository, Role::User);
+        let client = client(&repository).await;
+        let rig = create_rig(&client, &user, &rig_data("Rig", &parts)).await;
+
+        let response = client.get(format!("/rigs/{}/valuation", rig.id)).dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+        let valuation = response.into_json::<RigValuation>().await.unwrap();
+        assert!(valuation.complete);
+        assert_eq!(valuation.total_price, Money::new(370_000, "BRL"));
+
+        // Before any component had a price
+        let response = client.get(format!("/rigs/{}/valuation?at=2000-01-01", rig.id)).dispatch().await;
+        let valuation = response.into_json::<RigValuation>().await.unwrap();
+        assert!(!valuation.complete);
+        assert!(valuation.components.iter().all(|item| item.price.is_none()));
+
+        let response = client.get(format!("/rigs/{}/valuation?at=yesterday", rig.id)).dispatch().await;
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+    }
+
+    #[rocket::async_test]
+    async fn create_validates_the_components() {
+        let repository = InMemoryRepository::new();
+        let parts = add_parts(&repository).await;
+        let (cpu, memory) = add_mismatched_memory(&repository).await;
+        let (_, user) = log_in(&repository, Role::User);
+        let client = client(&repository).await;
+
+        let response = client.post("/rigs").json(&rig_data("Rig", &parts)).dispatch().await;
+        assert_eq!(response.status(), Status::Unauthorized);
+
+        // No storage or memory
+        let response = client.post("/rigs").header(user.clone()).json(&rig_data("Rig", &parts[..2])).dispatch().await;
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+        let error = response.into_json::<ApiError>().await.unwrap();
+        assert!(!error.fields.is_empty());
+        assert!(error.fields.iter().all(|error| error.field.starts_with("components")), "{error}");
+
+        let mismatched = [cpu, parts[1].clone(), memory, parts[3].clone()];
+        let response = client.post("/rigs").header(user.clone()).json(&rig_data("Rig", &mismatched)).dispatch().await;
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+
+        let rig = CreateRigData { allow_incompatible: true, ..rig_data("Rig", &mismatched) };
+        let created = create_rig(&client, &user, &rig).await;
+        assert_eq!(created.components.len(), 4);
+    }
+
+    #[rocket::async_test]
+    async fn create_rejects_prices_too_large_to_add_up() {
+        let repository = InMemoryRepository::new();
+        let parts = add_parts(&repository).await;
+        let storage = add_component(&repository, "storage", "S9", Money::new(i64::MAX / 2, "BRL")).await;
+        let (_, user) = log_in(&repository, Role::User);
+        let client = client(&repository).await;
+
+        let mut rig = rig_data("Rig", &parts);
+        rig.components[3].quantity = 1_001;
+        let response = client.post("/rigs").header(user.clone()).json(&rig).dispatch().await;
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+        let error = response.into_json::<ApiError>().await.unwrap();
+        assert_eq!(error.fields[0].field, "components[3].quantity");
+
+        // Each line fits, but not their sum
+        let rig = rig_data("Rig", &[parts[0].clone(), parts[1].clone(), parts[2].clone(), storage.clone()]);
+        let mut rig = CreateRigData { allow_incompatible: true, ..rig };
+        rig.components[3].quantity = 2;
+        let response = client.post("/rigs").header(user.clone()).json(&rig).dispatch().await;
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+        let error = response.into_json::<ApiError>().await.unwrap();
+        assert!(error.fields.iter().any(|error| error.message == "total price in BRL is too large"), "{error}");
+    }
+
+    #[rocket::async_test]
+    async fn check_compatibility_reports_mismatches() {
+        let repository = InMemoryRepository::new();
+        let parts = add_parts(&repository).await;
+        let (cpu, memory) = add_mismatched_memory(&repository).await;
+        let client = client(&repository).await;
+
+        let response = client.post("/rigs/compatibility").json(&rig_data("Rig", &parts)).dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+        assert!(response.into_json::<CompatibilityReport>().await.unwrap().compatible);
+
+        let response = client.post("/rigs/compatibility").json(&rig_data("Rig", &[cpu.clone(), memory.clone()])).dispatch().await;
+        let report = response.into_json::<CompatibilityReport>().await.unwrap();
+        assert!(!report.compatible);
+        assert_eq!(report.incompatibilities[0].rule, "memory_generation");
+        assert_eq!(report.incompatibilities[0].components, [cpu.id, memory.id]);
+        assert_eq!(report.incompatibilities[0].message, "cpu Acme C5 works with ddr5 memory but memory Acme M4 works with ddr4");
+    }
+
+    #[rocket::async_test]
+    async fn rename_is_for_the_owner() {
+        let repository = InMemoryRepository::new();
+        let parts = add_parts(&repository).await;
+        let (_, owner) = log_in(&repository, Role::User);
+        let (_, other) = log_in(&repository, Role::User);
+        let (_, admin) = log_in(&repository, Role::Admin);
+        let client = client(&repository).await;
+        let rig = create_rig(&client, &owner, &rig_data("Rig", &parts)).await;
+        let uri = format!("/rigs/{}", rig.id);
+
+        let response = client.patch(&uri).header(other).json(&json!({ "name": "Mine" })).dispatch().await;
+        assert_eq!(response.status(), Status::Forbidden);
+
+        let response = client.patch(&uri).header(owner.clone()).json(&json!({ "name": "" })).dispatch().await;
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+
+        let response = client.patch(&uri).header(owner).json(&json!({ "name": "Renamed" })).dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.into_json::<Rig>().await.unwrap().name, "Renamed");
+
+        let response = client.patch(&uri).header(admin).json(&json!({ "name": "Moderated" })).dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+    }
+
+    #[rocket::async_test]
+    async fn update_components_replaces_them() {
+        let repository = InMemoryRepository::new();
+        let parts = add_parts(&repository).await;
+        let gpu = add_component(&repository, "gpu", "G2", Money::new(300_000, "BRL")).await;
+        let (_, owner) = log_in(&repository, Role::User);
+        let client = client(&repository).await;
+        let rig = create_rig(&client, &owner, &rig_data("Rig", &parts)).await;
+
+        let mut components = rig_data("Rig", &[parts[0].clone(), gpu.clone(), parts[2].clone(), parts[3].clone()]).components;
+        components[2].quantity = 2;
+        let response = client.put(format!("/rigs/{}/components", rig.id))
+            .header(owner.clone())
+            .json(&json!({ "components": components }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Ok);
+        let updated = response.into_json::<RigWithComponents>().await.unwrap();
+        assert_eq!(updated.total_price, Money::new(500_000, "BRL"));
+        assert!(updated.components.iter().any(|item| item.component.id == gpu.id));
+
+        let response = client.put(format!("/rigs/{}/components", Uuid::new_v4()))
+            .header(owner)
+            .json(&json!({ "components": components }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::NotFound);
+    }
+
+    #[rocket::async_test]
+    async fn clone_swaps_in_the_overrides() {
+        let repository = InMemoryRepository::new();
+        let parts = add_parts(&repository).await;
+        let gpu = add_component(&repository, "gpu", "G2", Money::new(300_000, "BRL")).await;
+        let (_, owner) = log_in(&repository, Role::User);
+        let (forker, other) = log_in(&repository, Role::User);
+        let client = client(&repository).await;
+        let rig = create_rig(&client, &owner, &rig_data("Rig", &parts)).await;
+
+        let response = client.post(format!("/rigs/{}/clone", rig.id))
+            .header(other.clone())
+            .json(&json!({ "name": "Fork", "components": [{ "component_id": gpu.id, "quantity": 1 }] }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Ok);
+        let clone = response.into_json::<RigWithComponents>().await.unwrap();
+        assert_eq!((clone.owner_id, clone.parent_rig_id), (Some(forker.id), Some(rig.id)));
+        assert_eq!(clone.components.len(), 4);
+        assert!(clone.components.iter().any(|item| item.component.id == gpu.id));
+        assert!(!clone.components.iter().any(|item| item.component.id == parts[1].id));
+
+        // Rejected forks aren't created
+        let response = client.post(format!("/rigs/{}/clone", rig.id))
+            .header(other.clone())
+            .json(&json!({ "name": "", "components": [{ "component_id": Uuid::new_v4(), "quantity": 1 }] }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+        assert_eq!(response.into_json::<ApiError>().await.unwrap().fields.len(), 2);
+        let rigs = client.get("/rigs").dispatch().await.into_json::<Vec<RigWithComponents>>().await.unwrap();
+        assert_eq!(rigs.len(), 2);
+
+        let response = client.post(format!("/rigs/{}/clone", Uuid::new_v4()))
+            .header(other)
+            .json(&json!({ "name": "Fork", "components": [] }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::NotFound);
+    }
+
+    #[rocket::async_test]
+    async fn shared_rigs_are_public_until_revoked() {
+        let repository = InMemoryRepository::new();
+        let parts = add_parts(&repository).await;
+        let (_, owner) = log_in(&repository, Role::User);
+        let (_, stranger) = log_in(&repository, Role::User);
+        let client = client(&repository).await;
+        let rig = create_rig(&client, &owner, &rig_data("Rig", &parts)).await;
+        let uri = format!("/rigs/{}/share", rig.id);
+
+        let response = client.post(&uri).header(owner.clone()).json(&json!({ "shared": true })).dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+        let slug = response.into_json::<Rig>().await.unwrap().share_slug.unwrap();
+
+        // Sharing again keeps the link
+        let response = client.post(&uri).header(owner.clone()).json(&json!({ "shared": true })).dispatch().await;
+        assert_eq!(response.into_json::<Rig>().await.unwrap().share_slug.as_ref(), Some(&slug));
+
+        let response = client.get(format!("/shared/{slug}")).dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.into_json::<RigWithComponents>().await.unwrap().id, rig.id);
+
+        // Only the owner gets to see the link
+        let response = client.get(format!("/rigs/{}", rig.id)).header(owner.clone()).dispatch().await;
+        assert_eq!(response.into_json::<RigWithComponents>().await.unwrap().share_slug.as_ref(), Some(&slug));
+        let response = client.get("/rigs?mine=true").header(owner.clone()).dispatch().await;
+        assert_eq!(response.into_json::<Vec<RigWithComponents>>().await.unwrap()[0].share_slug.as_ref(), Some(&slug));
+
+        let response = client.get(format!("/rigs/{}", rig.id)).header(stranger.clone()).dispatch().await;
+        assert_eq!(response.into_json::<RigWithComponents>().await.unwrap().share_slug, None);
+        let response = client.get("/rigs").dispatch().await;
+        assert_eq!(response.into_json::<Vec<RigWithComponents>>().await.unwrap()[0].share_slug, None);
+        let response = client.get("/rigs/export").header(stranger).dispatch().await;
+        assert_eq!(response.into_json::<Vec<RigWithComponents>>().await.unwrap()[0].share_slug, None);
+
+        let response = client.post(&uri).header(owner).json(&json!({ "shared": false })).dispatch().await;
+        assert_eq!(response.into_json::<Rig>().await.unwrap().share_slug, None);
+
+        let response = client.get(format!("/shared/{slug}")).dispatch().await;
+        assert_eq!(response.status(), Status::NotFound);
+    }
+
+    #[rocket::async_test]
+    async fn delete_detaches_the_clones() {
+        let repository = InMemoryRepository::new();
+        let parts = add_parts(&repository).await;
+        let (_, owner) = log_in(&repository, Role::User);
+        let (_, other) = log_in(&repository, Role::User);
+        let client = client(&repository).await;
+        let rig = create_rig(&client, &owner, &rig_data("Rig", &parts)).await;
+        let response = client.post(format!("/rigs/{}/clone", rig.id)).header(other.clone()).json(&json!({ "name": "Fork" })).dispatch().await;
+        let clone = response.into_json::<RigWithComponents>().await.unwrap();
+        let uri = format!("/rigs/{}", rig.id);
+
+        let response = client.delete(&uri).header(other).dispatch().await;
+        assert_eq!(response.status(), Status::Forbidden);
 
-    let component_ids: Vec<Uuid> = rig_components
-        .into_iter()
-        .map(|rc| rc.component_id)
-        .collect();
+        let response = client.delete(&uri).header(owner.clone()).dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
 
-    let components = component::list_components_by_id(&component_ids, &mut db)
-        .await?;
+        let response = client.get(format!("/rigs/{}", clone.id)).dispatch().await;
+        assert_eq!(response.into_json::<RigWithComponents>().await.unwrap().parent_rig_id, None);
 
-    Ok(Json(rig.with_components(components)))
+        let response = client.delete(&uri).header(owner).dispatch().await;
+        assert_eq!(response.status(), Status::NotFound);
+    }
 }
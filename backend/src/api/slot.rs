@@ -0,0 +1,153 @@
+use api_types::slot::{CreateSlotData, Slot, UpdateSlotData};
+use rocket::{delete, fairing::AdHoc, get, post, put, routes, serde::json::Json, State};
+
+use crate::{
+    auth::{Admin, RequireRole},
+    error::{ApiError, ApiResult},
+    repository::SlotRepository,
+    validation::{self, ValidationErrors},
+};
+
+pub fn stage() -> AdHoc {
+    AdHoc::on_ignite("Slot Routes", |rocket| async {
+        rocket.mount("/slots", routes![list, detail, create, update, delete])
+    })
+}
+
+#[utoipa::path(
+    context_path = "/slots",
+    tag = "slots",
+    operation_id = "list_slots",
+    responses(
+        (status = 200, description = "Every slot", body = Vec<Slot>),
+    ),
+)]
+#[get("/")]
+pub async fn list(slots: &State<Box<dyn SlotRepository>>) -> ApiResult<Json<Vec<Slot>>> {
+    Ok(Json(slots.list_slots().await?))
+}
+
+#[utoipa::path(
+    context_path = "/slots",
+    tag = "slots",
+    operation_id = "get_slot",
+    params(
+        ("key" = String, Path, description = "Slot key"),
+    ),
+    responses(
+        (status = 200, description = "The slot", body = Slot),
+        (status = 404, description = "No such slot", body = ApiError),
+    ),
+)]
+#[get("/<key>")]
+pub async fn detail(key: &str, slots: &State<Box<dyn SlotRepository>>) -> ApiResult<Json<Slot>> {
+    Ok(Json(slots.get_slot(key).await?))
+}
+
+#[utoipa::path(
+    context_path = "/slots",
+    tag = "slots",
+    operation_id = "create_slot",
+    responses(
+        (status = 200, description = "The created slot", body = Slot),
+        (status = 401, description = "Missing or invalid bearer token", body = ApiError),
+        (status = 403, description = "Only admins may do it", body = ApiError),
+        (status = 409, description = "A slot with the key already exists", body = ApiError),
+        (status = 422, description = "Malformed key, empty name or icon, or per-rig bounds where min is negative or max is below 1 or min", body = ApiError),
+    ),
+    security(("bearer_auth" = [])),
+)]
+#[post("/", data = "<slot>")]
+pub async fn create(_admin: RequireRole<Admin>, slot: Json<CreateSlotData>, slots: &State<Box<dyn SlotRepository>>) -> ApiResult<Json<Slot>> {
+    let mut errors = ValidationErrors::default();
+    validation::slot::validate_key(&slot.key, &mut errors);
+    validation::slot::validate_slot(&slot.slot, &mut errors);
+    errors.into_result()?;
+
+    Ok(Json(slots.create_slot(slot.0.into()).await?))
+}
+
+#[utoipa::path(
+    context_path = "/slots",
+    tag = "slots",
+    operation_id = "update_slot",
+    params(
+        ("key" = String, Path, description = "Slot key"),
+    ),
+    responses(
+        (status = 200, description = "The updated slot", body = Slot),
+        (status = 401, description = "Missing or invalid bearer token", body = ApiError),
+        (status = 403, description = "Only admins may do it", body = ApiError),
+        (status = 404, description = "No such slot", body = ApiError),
+        (status = 422, description = "Empty name or icon, or per-rig bounds where min is negative or max is below 1 or min", body = ApiError),
+    ),
+    security(("bearer_auth" = [])),
+)]
+#[put("/<key>", data = "<slot>")]
+pub async fn update(_admin: RequireRole<Admin>, key: &str, slot: Json<UpdateSlotData>, slots: &State<Box<dyn SlotRepository>>) -> ApiResult<Json<Slot>> {
+    let mut errors = ValidationErrors::default();
+    validation::slot::validate_slot(&slot, &mut errors);
+    errors.into_result()?;
+
+    Ok(Json(slots.update_slot(slot.0.into_slot(key.to_owned())).await?))
+}
+
+#[utoipa::path(
+    context_path = "/slots",
+    tag = "slots",
+    operation_id = "delete_slot",
+    params(
+        ("key" = String, Path, description = "Slot key"),
+    ),
+    responses(
+        (status = 200, description = "The slot was deleted"),
+        (status = 401, description = "Missing or invalid bearer token", body = ApiError),
+        (status = 403, description = "Only admins may do it", body = ApiError),
+        (status = 404, description = "No such slot", body = ApiError),
+        (status = 409, description = "Components still fit in the slot, listed in `details`", body = ApiError),
+    ),
+    security(("bearer_auth" = [])),
+)]
+#[delete("/<key>")]
+pub async fn delete(_admin: RequireRole<Admin>, key: &str, slots: &State<Box<dyn SlotRepository>>) -> ApiResult<()> {
+    let components = slots.delete_slot(key).await?;
+    if !components.is_empty() {
+        return Err(
+            ApiError::conflict("slot still has components")
+                .with_details(components)
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use api_types::{money::Money, types::Role};
+    use rocket::http::Status;
+
+    use crate::{
+        api::testing::{add_component, client, log_in},
+        repository::{memory::InMemoryRepository, SlotRepository},
+    };
+
+    #[rocket::async_test]
+    async fn delete_refuses_slots_with_components() {
+        let repository = InMemoryRepository::new();
+        let cpu = add_component(&repository, "cpu", "C1", Money::new(100_000, "BRL")).await;
+        let client = client(&repository).await;
+        let (_, admin) = log_in(&repository, Role::Admin);
+
+        let response = client.delete("/slots/cpu").header(admin.clone()).dispatch().await;
+        assert_eq!(response.status(), Status::Conflict);
+        assert!(response.into_string().await.unwrap().contains(&cpu.id.to_string()));
+        assert!(repository.get_slot("cpu").await.is_ok());
+
+        let response = client.delete("/slots/storage").header(admin.clone()).dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+        assert!(repository.get_slot("storage").await.is_err());
+
+        let response = client.delete("/slots/storage").header(admin).dispatch().await;
+        assert_eq!(response.status(), Status::NotFound);
+    }
+}
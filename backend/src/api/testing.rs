@@ -0,0 +1,99 @@
+//! What the routes' tests share: a local client over an
+//! [`InMemoryRepository`], users to call it as and a catalog to build rigs
+//! out of
+
+use api_types::{
+    component::Component,
+    money::Money,
+    rig::{CreateRigData, RigComponentData, RigWithComponents},
+    types::{MemoryGeneration, Role},
+    user::User,
+};
+use rocket::{
+    http::{Header, Status},
+    local::asynchronous::Client,
+};
+use uuid::Uuid;
+
+use crate::{
+    error,
+    repository::{self, memory::InMemoryRepository, ComponentRepository},
+};
+
+/// A client over every repository-backed route, storing everything in
+/// `repository`
+pub async fn client(repository: &InMemoryRepository) -> Client {
+    let rocket = repository::manage(rocket::build(), repository.clone())
+        .attach(error::stage())
+        .attach(super::component::stage())
+        .attach(super::rig::stage())
+        .attach(super::revision::stage())
+        .attach(super::slot::stage())
+        .attach(super::user::stage())
+        .attach(super::currency::stage());
+
+    Client::tracked(rocket).await.expect("rocket ignites")
+}
+
+/// Logs a new user with `role` in, returning it and the header that
+/// authenticates as it
+pub fn log_in(repository: &InMemoryRepository, role: Role) -> (User, Header<'static>) {
+    let user = User {
+        role,
+        ..User::new(format!("{}@example.com", Uuid::new_v4()), String::new())
+    };
+    let token = repository.log_in(user.clone());
+
+    (user, Header::new("Authorization", format!("Bearer {token}")))
+}
+
+pub async fn add_component(repository: &InMemoryRepository, slot: &str, model: &str, price: Money) -> Component {
+    repository
+        .create_component(Component::new("Acme".to_owned(), model.to_owned(), slot.to_owned(), price))
+        .await
+        .expect("the component is valid")
+}
+
+/// One component for every slot, which together make a valid rig
+pub async fn add_parts(repository: &InMemoryRepository) -> Vec<Component> {
+    vec![
+        add_component(repository, "cpu", "C1", Money::new(100_000, "BRL")).await,
+        add_component(repository, "gpu", "G1", Money::new(200_000, "BRL")).await,
+        add_component(repository, "memory", "M1", Money::new(30_000, "BRL")).await,
+        add_component(repository, "storage", "S1", Money::new(40_000, "BRL")).await,
+    ]
+}
+
+/// A CPU and memory that don't share a memory generation
+pub async fn add_mismatched_memory(repository: &InMemoryRepository) -> (Component, Component) {
+    let cpu = Component {
+        memory_generations: vec![MemoryGeneration::Ddr5],
+        ..Component::new("Acme".to_owned(), "C5".to_owned(), "cpu".to_owned(), Money::new(100_000, "BRL"))
+    };
+    let memory = Component {
+        memory_generations: vec![MemoryGeneration::Ddr4],
+        ..Component::new("Acme".to_owned(), "M4".to_owned(), "memory".to_owned(), Money::new(30_000, "BRL"))
+    };
+
+    (repository.create_component(cpu).await.unwrap(), repository.create_component(memory).await.unwrap())
+}
+
+/// A rig of `components`, one of each
+pub fn rig_data(name: &str, components: &[Component]) -> CreateRigData {
+    CreateRigData {
+        name: name.to_owned(),
+        components: components
+            .iter()
+            .map(|component| RigComponentData { component_id: component.id, quantity: 1 })
+            .collect(),
+        allow_incompatible: false,
+    }
+}
+
+/// Creates `rig` as `user`, which must succeed
+pub async fn create_rig(client: &Client, user: &Header<'static>, rig: &CreateRigData) -> RigWithComponents {
+    let response = client.post("/rigs").header(user.clone()).json(rig).dispatch().await;
+    assert_eq!(response.status(), Status::Ok);
+
+    response.into_json().await.unwrap()
+}
@@ -0,0 +1,218 @@
+use api_types::user::{CredentialsData, SessionData, UpdateRoleData, User};
+use rocket::{fairing::AdHoc, get, post, put, routes, serde::json::Json, tokio::task, State};
+use rocket_db_pools::diesel::result::Error;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::{
+    auth::{self, Admin, AuthenticatedUser, RequireRole},
+    error::{ApiError, ApiResult},
+    models::user::Session,
+    repository::UserRepository,
+    validation::{self, ValidationErrors},
+};
+
+pub fn stage() -> AdHoc {
+    AdHoc::on_ignite("Auth Routes", |rocket| async {
+        rocket
+            .mount("/auth", routes![register, login, logout, me])
+            .mount("/users", routes![list, update_role])
+    })
+}
+
+#[utoipa::path(
+    context_path = "/auth",
+    tag = "users",
+    operation_id = "register",
+    responses(
+        (status = 200, description = "The registered user", body = User),
+        (status = 409, description = "The email is taken", body = ApiError),
+        (status = 422, description = "Malformed email, or a password too short or too long", body = ApiError),
+    ),
+)]
+#[post("/register", data = "<credentials>")]
+pub async fn register(credentials: Json<CredentialsData>, users: &State<Box<dyn UserRepository>>) -> ApiResult<Json<User>> {
+    let CredentialsData { email, password } = credentials.0;
+    let email = auth::normalize_email(&email);
+
+    let mut errors = ValidationErrors::default();
+    validation::user::validate_credentials(&email, &password, &mut errors);
+    errors.into_result()?;
+
+    // Hashing is deliberately slow, so it stays off the async workers
+    let password_hash = task::spawn_blocking(move || auth::hash_password(&password))
+        .await
+        .map_err(|_| ApiError::internal())?
+        .map_err(|_| ApiError::internal())?;
+
+    Ok(Json(users.create_user(User::new(email, password_hash)).await?))
+}
+
+#[utoipa::path(
+    context_path = "/auth",
+    tag = "users",
+    operation_id = "login",
+    responses(
+        (status = 200, description = "A session token", body = SessionData),
+        (status = 401, description = "Invalid email or password", body = ApiError),
+    ),
+)]
+#[post("/login", data = "<credentials>")]
+pub async fn login(credentials: Json<CredentialsData>, users: &State<Box<dyn UserRepository>>) -> ApiResult<Json<SessionData>> {
+    let CredentialsData { email, password } = credentials.0;
+    let invalid_credentials = || ApiError::unauthorized("invalid email or password");
+
+    let found = match users.find_user_by_email(&auth::normalize_email(&email)).await {
+        Ok(found) => found,
+        Err(rocket::response::Debug(Error::NotFound)) => return Err(invalid_credentials()),
+        Err(err) => return Err(err.into()),
+    };
+
+    let password_hash = found.password_hash.clone();
+    let verified = task::spawn_blocking(move || auth::verify_password(&password, &password_hash))
+        .await
+        .map_err(|_| ApiError::internal())?;
+
+    if !verified {
+        return Err(invalid_credentials());
+    }
+
+    users.delete_expired_sessions().await?;
+
+    let token = auth::generate_token();
+    let now = OffsetDateTime::now_utc();
+    let session = users.create_session(Session {
+        token_hash: auth::hash_token(&token),
+        user_id: found.id,
+        created_at: now,
+        expires_at: now + auth::SESSION_TTL,
+    }).await?;
+
+    Ok(Json(SessionData {
+        token,
+        expires_at: session.expires_at,
+        user: found,
+    }))
+}
+
+#[utoipa::path(
+    context_path = "/auth",
+    tag = "users",
+    operation_id = "logout",
+    responses(
+        (status = 200, description = "The session was ended"),
+        (status = 401, description = "Missing or invalid bearer token", body = ApiError),
+    ),
+    security(("bearer_auth" = [])),
+)]
+#[post("/logout")]
+pub async fn logout(current: AuthenticatedUser, users: &State<Box<dyn UserRepository>>) -> ApiResult<()> {
+    users.delete_session(&current.token_hash).await?;
+
+    Ok(())
+}
+
+#[utoipa::path(
+    context_path = "/auth",
+    tag = "users",
+    operation_id = "get_current_user",
+    responses(
+        (status = 200, description = "The authenticated user", body = User),
+        (status = 401, description = "Missing or invalid bearer token", body = ApiError),
+    ),
+    security(("bearer_auth" = [])),
+)]
+#[get("/me")]
+pub async fn me(current: AuthenticatedUser) -> Json<User> {
+    Json(current.user)
+}
+
+#[utoipa::path(
+    context_path = "/users",
+    tag = "users",
+    operation_id = "list_users",
+    responses(
+        (status = 200, description = "Every user", body = Vec<User>),
+        (status = 401, description = "Missing or invalid bearer token", body = ApiError),
+        (status = 403, description = "Only admins may do it", body = ApiError),
+    ),
+    security(("bearer_auth" = [])),
+)]
+#[get("/")]
+pub async fn list(_admin: RequireRole<Admin>, users: &State<Box<dyn UserRepository>>) -> ApiResult<Json<Vec<User>>> {
+    Ok(Json(users.list_users().await?))
+}
+
+#[utoipa::path(
+    context_path = "/users",
+    tag = "users",
+    operation_id = "update_user_role",
+    params(
+        ("id" = Uuid, Path, description = "User id"),
+    ),
+    responses(
+        (status = 200, description = "The user with the new role", body = User),
+        (status = 400, description = "Malformed id", body = ApiError),
+        (status = 401, description = "Missing or invalid bearer token", body = ApiError),
+        (status = 403, description = "Only admins may do it", body = ApiError),
+        (status = 404, description = "No such user", body = ApiError),
+        (status = 409, description = "The last admin can't be demoted", body = ApiError),
+    ),
+    security(("bearer_auth" = [])),
+)]
+#[put("/<id>/role", data = "<role>")]
+pub async fn update_role(_admin: RequireRole<Admin>, id: &str, role: Json<UpdateRoleData>, users: &State<Box<dyn UserRepository>>) -> ApiResult<Json<User>> {
+    let id = Uuid::parse_str(id)?;
+
+    users.set_role(id, role.role).await?
+        .map(Json)
+        .ok_or_else(|| ApiError::conflict("the last admin can't be demoted"))
+}
+
+#[cfg(test)]
+mod tests {
+    use api_types::{types::Role, user::User};
+    use rocket::{http::Status, serde::json::serde_json::json};
+
+    use crate::{
+        api::testing::{client, log_in},
+        repository::memory::InMemoryRepository,
+    };
+
+    #[rocket::async_test]
+    async fn update_role_keeps_the_last_admin() {
+        let repository = InMemoryRepository::new();
+        let (first, first_header) = log_in(&repository, Role::Admin);
+        let (second, _) = log_in(&repository, Role::Admin);
+        let client = client(&repository).await;
+
+        let response = client.put(format!("/users/{}/role", second.id))
+            .header(first_header.clone())
+            .json(&json!({ "role": "editor" }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.into_json::<User>().await.unwrap().role, Role::Editor);
+
+        let response = client.put(format!("/users/{}/role", first.id))
+            .header(first_header.clone())
+            .json(&json!({ "role": "user" }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Conflict);
+
+        let response = client.put(format!("/users/{}/role", first.id))
+            .header(first_header.clone())
+            .json(&json!({ "role": "admin" }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Ok);
+
+        let response = client.put(format!("/users/{}/role", uuid::Uuid::new_v4()))
+            .header(first_header)
+            .json(&json!({ "role": "user" }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::NotFound);
+    }
+}
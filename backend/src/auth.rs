@@ -0,0 +1,234 @@
+use std::marker::PhantomData;
+
+use api_types::{types::Role, user::User};
+use argon2::{
+    password_hash::{self, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Argon2,
+};
+use rand_core::{OsRng, RngCore};
+use rocket::{
+    fairing::AdHoc,
+    request::{FromRequest, Outcome},
+    response::Debug,
+    serde::json::serde_json::json,
+    Request, State,
+};
+use rocket_db_pools::{diesel::result::{DatabaseErrorKind, Error}, Database};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use time::Duration;
+use uuid::Uuid;
+
+use crate::{
+    database::Db,
+    error::{ApiError, ApiResult},
+    repository::{self, SessionRepository},
+    validation::{self, ValidationErrors},
+};
+
+/// How long a session token stays valid after login
+pub const SESSION_TTL: Duration = Duration::days(30);
+
+pub fn hash_password(password: &str) -> password_hash::Result<String> {
+    let salt = SaltString::generate(&mut OsRng);
+
+    Ok(Argon2::default().hash_password(password.as_bytes(), &salt)?.to_string())
+}
+
+pub fn verify_password(password: &str, password_hash: &str) -> bool {
+    PasswordHash::new(password_hash)
+        .is_ok_and(|hash| Argon2::default().verify_password(password.as_bytes(), &hash).is_ok())
+}
+
+/// A random session token, hex encoded
+pub fn generate_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+
+    hex(&bytes)
+}
+
+/// What's stored in place of a token, so a leaked table can't be used to log in
+pub fn hash_token(token: &str) -> String {
+    hex(&Sha256::digest(token.as_bytes()))
+}
+
+/// Emails are compared case-insensitively
+pub fn normalize_email(email: &str) -> String {
+    email.trim().to_lowercase()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// The user behind the request's `Authorization: Bearer <token>` header.
+/// Requests without a valid, unexpired session fail with a 401.
+pub struct AuthenticatedUser {
+    pub user: User,
+    pub token_hash: String,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for AuthenticatedUser {
+    type Error = ApiError;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let token = request.headers()
+            .get_one("Authorization")
+            .and_then(|header| header.strip_prefix("Bearer "));
+
+        let Some(token) = token else {
+            return ApiError::unauthorized("a bearer token is required").fail(request);
+        };
+
+        let sessions = match request.guard::<&State<Box<dyn SessionRepository>>>().await {
+            Outcome::Success(sessions) => sessions,
+            _ => return ApiError::internal().fail(request),
+        };
+
+        let token_hash = hash_token(token);
+        match sessions.get_session_user(&token_hash).await {
+            Ok(user) => Outcome::Success(Self { user, token_hash }),
+            Err(rocket::response::Debug(Error::NotFound)) => {
+                ApiError::unauthorized("the session is invalid or has expired").fail(request)
+            },
+            Err(err) => ApiError::from(err).fail(request),
+        }
+    }
+}
+
+impl AuthenticatedUser {
+    /// Admins manage every rig, everyone else only the ones they own
+    pub fn can_manage(&self, owner_id: Option<Uuid>) -> bool {
+        self.user.role == Role::Admin || owner_id == Some(self.user.id)
+    }
+
+    pub fn ensure_can_manage(&self, owner_id: Option<Uuid>) -> ApiResult<()> {
+        if self.can_manage(owner_id) {
+            Ok(())
+        } else {
+            Err(ApiError::forbidden("only the owner of the rig can change it"))
+        }
+    }
+}
+
+/// A role [`RequireRole`] can ask for
+pub trait RequiredRole: Send + Sync + 'static {
+    const ROLE: Role;
+}
+
+pub struct Admin;
+
+impl RequiredRole for Admin {
+    const ROLE: Role = Role::Admin;
+}
+
+pub struct Editor;
+
+impl RequiredRole for Editor {
+    const ROLE: Role = Role::Editor;
+}
+
+/// An [`AuthenticatedUser`] with at least the role `R`.
+/// Users with a lesser role fail with a 403.
+pub struct RequireRole<R: RequiredRole> {
+    pub current: AuthenticatedUser,
+    role: PhantomData<R>,
+}
+
+#[rocket::async_trait]
+impl<'r, R: RequiredRole> FromRequest<'r> for RequireRole<R> {
+    type Error = ApiError;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let current = match AuthenticatedUser::from_request(request).await {
+            Outcome::Success(current) => current,
+            Outcome::Error(error) => return Outcome::Error(error),
+            Outcome::Forward(status) => return Outcome::Forward(status),
+        };
+
+        if current.user.role < R::ROLE {
+            let details = json!({
+                "required_role": R::ROLE,
+                "role": current.user.role,
+            });
+
+            return ApiError::forbidden(format!("requires the {} role", R::ROLE.as_str()))
+                .with_details(details)
+                .fail(request);
+        }
+
+        Outcome::Success(Self { current, role: PhantomData })
+    }
+}
+
+/// The `bootstrap_admin` config, e.g. in `Rocket.toml`:
+///
+/// ```toml
+/// [default.bootstrap_admin]
+/// email = "admin@example.com"
+/// password = "change-me-please"
+/// ```
+#[derive(Deserialize)]
+struct BootstrapAdmin {
+    email: String,
+    password: String,
+}
+
+/// Creates the configured `bootstrap_admin` as an admin on launch while there
+/// is no admin at all, so fresh deployments aren't locked out. An existing
+/// account with its email is never promoted: anyone could have registered it.
+pub fn stage() -> AdHoc {
+    AdHoc::try_on_ignite("Bootstrap Admin", |rocket| async {
+        if rocket.figment().find_value("bootstrap_admin").is_err() {
+            return Ok(rocket);
+        }
+
+        let config = match rocket.figment().extract_inner::<BootstrapAdmin>("bootstrap_admin") {
+            Ok(config) => config,
+            Err(err) => {
+                rocket::error!("invalid bootstrap_admin config: {err}");
+                return Err(rocket);
+            },
+        };
+
+        match bootstrap_admin(config, &rocket).await {
+            Ok(()) => Ok(rocket),
+            Err(err) => {
+                rocket::error!("could not bootstrap the admin: {err}");
+                Err(rocket)
+            },
+        }
+    })
+}
+
+async fn bootstrap_admin(config: BootstrapAdmin, rocket: &rocket::Rocket<rocket::Build>) -> Result<(), String> {
+    let BootstrapAdmin { email, password } = config;
+    let email = normalize_email(&email);
+
+    let mut errors = ValidationErrors::default();
+    validation::user::validate_credentials(&email, &password, &mut errors);
+    if let Some(error) = errors.fields.first() {
+        return Err(format!("{}: {}", error.field, error.message));
+    }
+
+    let db = Db::fetch(rocket).ok_or("the database is not attached")?;
+    let mut connection = db.get().await.map_err(|err| err.to_string())?;
+
+    if repository::user::count_admins(&mut connection).await.map_err(|err| format!("{:?}", err.0))? > 0 {
+        return Ok(());
+    }
+
+    let password_hash = hash_password(&password).map_err(|err| err.to_string())?;
+    let admin = match repository::user::create_admin(User::new(email, password_hash), &mut connection).await {
+        Ok(admin) => admin,
+        Err(Debug(Error::DatabaseError(DatabaseErrorKind::UniqueViolation, _))) => {
+            return Err("its email is already registered, and that account won't be promoted: use another email".to_owned());
+        },
+        Err(err) => return Err(format!("{:?}", err.0)),
+    };
+
+    rocket::info!("bootstrapped {} as admin", admin.email);
+    Ok(())
+}
@@ -0,0 +1,26 @@
+use api_types::{compatibility::{CompatibilityReport, Incompatibility}, component::Component};
+
+mod rules;
+
+pub trait Rule: Sync {
+    fn check(&self, components: &[Component]) -> Vec<Incompatibility>;
+}
+
+const RULES: &[&dyn Rule] = &[
+    &rules::SameSocket,
+    &rules::SharedMemoryGeneration,
+    &rules::SupportedInterface,
+];
+
+/// Runs every known [`Rule`] against a candidate set of components.
+pub fn evaluate(components: &[Component]) -> CompatibilityReport {
+    let incompatibilities: Vec<Incompatibility> = RULES
+        .iter()
+        .flat_map(|rule| rule.check(components))
+        .collect();
+
+    CompatibilityReport {
+        compatible: incompatibilities.is_empty(),
+        incompatibilities,
+    }
+}
@@ -0,0 +1,108 @@
+use std::collections::BTreeSet;
+
+use api_types::{component::Component, types::{Interface, MemoryGeneration}};
+
+use super::{Incompatibility, Rule};
+
+fn describe(component: &Component) -> String {
+    format!("{} {} {}", component.slot, component.manufacturer, component.model)
+}
+
+/// Lists names the way the API writes them, e.g. `ddr4, ddr5`
+fn list<'a>(names: impl IntoIterator<Item = &'a str>) -> String {
+    names.into_iter().collect::<Vec<_>>().join(", ")
+}
+
+/// Iterates over every unordered pair of components.
+fn pairs(components: &[Component]) -> impl Iterator<Item = (&Component, &Component)> {
+    components
+        .iter()
+        .enumerate()
+        .flat_map(move |(i, a)| components[i + 1..].iter().map(move |b| (a, b)))
+}
+
+/// Every component that declares a socket must declare the same one.
+pub struct SameSocket;
+
+impl Rule for SameSocket {
+    fn check(&self, components: &[Component]) -> Vec<Incompatibility> {
+        pairs(components)
+            .filter_map(|(a, b)| {
+                let (socket_a, socket_b) = (a.socket.as_ref()?, b.socket.as_ref()?);
+
+                (!socket_a.eq_ignore_ascii_case(socket_b)).then(|| Incompatibility {
+                    rule: "socket".to_owned(),
+                    components: vec![a.id, b.id],
+                    message: format!(
+                        "{} uses socket {socket_a} but {} uses socket {socket_b}",
+                        describe(a),
+                        describe(b),
+                    ),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Components that declare memory generations must have at least one in common.
+pub struct SharedMemoryGeneration;
+
+impl Rule for SharedMemoryGeneration {
+    fn check(&self, components: &[Component]) -> Vec<Incompatibility> {
+        pairs(components)
+            .filter(|(a, b)| !a.memory_generations.is_empty() && !b.memory_generations.is_empty())
+            .filter(|(a, b)| !a.memory_generations.iter().any(|generation| b.memory_generations.contains(generation)))
+            .map(|(a, b)| Incompatibility {
+                rule: "memory_generation".to_owned(),
+                components: vec![a.id, b.id],
+                message: format!(
+                    "{} works with {} memory but {} works with {}",
+                    describe(a),
+                    list(a.memory_generations.iter().map(MemoryGeneration::as_str)),
+                    describe(b),
+                    list(b.memory_generations.iter().map(MemoryGeneration::as_str)),
+                ),
+            })
+            .collect()
+    }
+}
+
+/// Components with a socket make up the platform and list the interfaces it
+/// provides. Every other component listing interfaces must be able to connect
+/// through at least one of them.
+pub struct SupportedInterface;
+
+impl Rule for SupportedInterface {
+    fn check(&self, components: &[Component]) -> Vec<Incompatibility> {
+        let (platform, devices): (Vec<&Component>, Vec<&Component>) = components
+            .iter()
+            .filter(|component| !component.interfaces.is_empty())
+            .partition(|component| component.socket.is_some());
+
+        let provided: BTreeSet<Interface> = platform
+            .iter()
+            .flat_map(|component| component.interfaces.iter().copied())
+            .collect();
+
+        if provided.is_empty() {
+            return Vec::new();
+        }
+
+        devices
+            .into_iter()
+            .filter(|device| !device.interfaces.iter().any(|interface| provided.contains(interface)))
+            .map(|device| Incompatibility {
+                rule: "interface".to_owned(),
+                components: std::iter::once(device.id)
+                    .chain(platform.iter().map(|component| component.id))
+                    .collect(),
+                message: format!(
+                    "{} needs one of {} but the platform only provides {}",
+                    describe(device),
+                    list(device.interfaces.iter().map(Interface::as_str)),
+                    list(provided.iter().map(Interface::as_str)),
+                ),
+            })
+            .collect()
+    }
+}
@@ -1,18 +1,49 @@
-use serde::{Deserialize, Serialize};
+use api_types::{component::UpdateComponentData, types::{Interface, MemoryGeneration}};
+use rocket_db_pools::diesel::{self, AsChangeset};
 
-use crate::{models::component::Component, types::Slot};
+/// [`UpdateComponentData`] spread over the table's columns
+#[derive(AsChangeset, Default)]
+#[diesel(table_name = api_types::schema::components)]
+pub struct ComponentChangeset {
+    pub manufacturer: Option<String>,
+    pub model: Option<String>,
+    pub slot: Option<String>,
+    pub price_cents: Option<i64>,
+    pub currency: Option<String>,
+    pub socket: Option<String>,
+    pub memory_generations: Option<Vec<MemoryGeneration>>,
+    pub interfaces: Option<Vec<Interface>>,
+}
 
-#[derive(Serialize, Deserialize)]
-pub struct CreateComponentData {
-    pub manufacturer: String,
-    pub model: String,
-    pub slot: Slot,
-    pub price: i32,
+impl ComponentChangeset {
+    pub fn is_empty(&self) -> bool {
+        self.manufacturer.is_none()
+            && self.model.is_none()
+            && self.slot.is_none()
+            && self.price_cents.is_none()
+            && self.currency.is_none()
+            && self.socket.is_none()
+            && self.memory_generations.is_none()
+            && self.interfaces.is_none()
+    }
 }
 
-impl From<CreateComponentData> for Component {
-    fn from(value: CreateComponentData) -> Self {
-        let CreateComponentData { manufacturer, model, slot, price } = value;
-        Self::new(manufacturer, model, slot, price)
+impl From<UpdateComponentData> for ComponentChangeset {
+    fn from(value: UpdateComponentData) -> Self {
+        let UpdateComponentData { manufacturer, model, slot, price, socket, memory_generations, interfaces } = value;
+        let (price_cents, currency) = price
+            .map(|price| (price.amount, price.currency))
+            .unzip();
+
+        Self {
+            manufacturer,
+            model,
+            slot,
+            price_cents,
+            currency,
+            socket,
+            memory_generations,
+            interfaces,
+        }
     }
 }
@@ -1,2 +1,3 @@
 pub mod component;
+pub mod revision;
 pub mod rig;
@@ -0,0 +1,19 @@
+use api_types::{money::Money, revision::{RevisionItem, RigRevisionWithComponents}};
+
+use crate::models::revision::RigRevision;
+
+impl RigRevision {
+    /// `components` must already be priced in `currency`
+    pub fn with_components(self, components: Vec<RevisionItem>, currency: &str) -> RigRevisionWithComponents {
+        let Self { number, name, created_at, .. } = self;
+        let total_price = Money::saturating_sum(currency, components.iter().map(|item| &item.line_total));
+
+        RigRevisionWithComponents {
+            number,
+            name,
+            created_at,
+            components,
+            total_price,
+        }
+    }
+}
@@ -1,53 +1,57 @@
+use std::collections::{HashMap, HashSet};
+
+use api_types::{component::Component, rig::{CloneRigData, Rig, RigComponentData, RigComponentItem}};
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use uuid::Uuid;
 
-use crate::models::{component::Component, rig::{Rig, RigComponent}};
-
-#[derive(Serialize, Deserialize)]
-pub struct CreateRigData {
-    pub name: String,
-    pub components: Vec<Uuid>,
-}
+use crate::models::rig::RigComponent;
 
-impl From<CreateRigData> for (Rig, Vec<RigComponent>) {
-    fn from(value: CreateRigData) -> Self {
-        let CreateRigData { name, components } = value;
-        let rig = Rig::new(name);
-        let rig_components = components
-            .into_iter()
-            .map(|component_id| RigComponent {
-                rig_id: rig.id, component_id
-            })
-            .collect();
+/// The clone's components: the overrides first, so validation errors point
+/// at them, followed by the original's components in every slot no override
+/// is in. `override_slots` maps the overrides to their slots; overrides
+/// missing from it are kept so validation reports them.
+pub fn merge_clone_components(data: &CloneRigData, original: Vec<(RigComponent, Component)>, override_slots: &HashMap<Uuid, String>) -> Vec<RigComponentData> {
+    let overridden: HashSet<&str> = override_slots.values().map(String::as_str).collect();
+    let kept = original
+        .into_iter()
+        .filter(|(_, component)| !overridden.contains(component.slot.as_str()))
+        .map(|(rig_component, _)| RigComponentData {
+            component_id: rig_component.component_id,
+            quantity: rig_component.quantity,
+        });
 
-        (rig, rig_components)
-    }
+    data.components.iter().copied().chain(kept).collect()
 }
 
-#[derive(Serialize, Deserialize)]
-pub struct RigWithComponents {
-    pub id: Uuid,
-    pub name: String,
-    pub components: Vec<Component>,
+/// A line of the rigs' CSV export: one per component of every rig
+#[derive(Serialize, Deserialize, ToSchema, Default)]
+pub struct RigComponentRow {
+    pub rig_id: Uuid,
+    pub rig_name: String,
+    pub slot: String,
+    pub component_id: Uuid,
+    pub manufacturer: String,
+    pub model: String,
+    pub quantity: i32,
+    pub price_cents: i64,
+    pub line_total_cents: i64,
+    pub currency: String,
 }
 
-impl Rig {
-    pub fn with_components(self, components: Vec<Component>) -> RigWithComponents {
-        let Self { id, name } = self;
-        RigWithComponents {
-            id,
-            name,
-            components,
+impl RigComponentRow {
+    pub fn new(rig: &Rig, item: &RigComponentItem) -> Self {
+        Self {
+            rig_id: rig.id,
+            rig_name: rig.name.clone(),
+            slot: item.component.slot.clone(),
+            component_id: item.component.id,
+            manufacturer: item.component.manufacturer.clone(),
+            model: item.component.model.clone(),
+            quantity: item.quantity,
+            price_cents: item.component.price.amount,
+            line_total_cents: item.line_total.amount,
+            currency: item.line_total.currency.clone(),
         }
     }
 }
-
-impl From<RigWithComponents> for (Rig, Vec<Component>) {
-    fn from(value: RigWithComponents) -> Self {
-        let RigWithComponents { id, name, components } = value;
-        (
-            Rig { id, name },
-            components,
-        )
-    }
-}
@@ -0,0 +1,204 @@
+use api_types::{compatibility::CompatibilityReport, error::FieldError};
+use rocket::{
+    catch, catchers,
+    fairing::AdHoc,
+    http::Status,
+    request::Outcome,
+    response::{self, Debug, Responder},
+    serde::json::{serde_json::{self, Value}, Json},
+    Request,
+};
+use rocket_db_pools::diesel::result::{DatabaseErrorKind, Error};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::validation::ValidationErrors;
+
+pub type ApiResult<T> = Result<T, ApiError>;
+
+/// Error body shared by every route and catcher of the API.
+///
+/// `code` is meant for programs, `message` for people. `fields` is only
+/// present for validation failures and `details` carries any extra payload
+/// an error needs to be acted upon (e.g. the rigs blocking a deletion).
+#[derive(Serialize, Debug, Clone, ToSchema)]
+pub struct ApiError {
+    #[serde(skip)]
+    pub status: Status,
+    pub code: &'static str,
+    pub message: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<FieldError>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<Value>,
+}
+
+impl ApiError {
+    pub fn new(status: Status, code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            code,
+            message: message.into(),
+            fields: Vec::new(),
+            details: None,
+        }
+    }
+
+    pub fn with_details(mut self, details: impl Serialize) -> Self {
+        self.details = serde_json::to_value(details).ok();
+        self
+    }
+
+    pub fn bad_request(message: impl Into<String>) -> Self {
+        Self::new(Status::BadRequest, "bad_request", message)
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(Status::NotFound, "not_found", message)
+    }
+
+    pub fn unauthorized(message: impl Into<String>) -> Self {
+        Self::new(Status::Unauthorized, "unauthorized", message)
+    }
+
+    pub fn forbidden(message: impl Into<String>) -> Self {
+        Self::new(Status::Forbidden, "forbidden", message)
+    }
+
+    pub fn conflict(message: impl Into<String>) -> Self {
+        Self::new(Status::Conflict, "conflict", message)
+    }
+
+    pub fn internal() -> Self {
+        Self::new(Status::InternalServerError, "internal_error", "internal server error")
+    }
+}
+
+impl ApiError {
+    /// Fails a request guard with this error. The catcher for its status
+    /// answers with it instead of a generic body.
+    pub fn fail<T>(self, request: &Request<'_>) -> Outcome<T, Self> {
+        let status = self.status;
+        request.local_cache(|| GuardError(Some(self.clone())));
+
+        Outcome::Error((status, self))
+    }
+}
+
+/// The error a request guard failed with, cached on the request for catchers
+#[derive(Default)]
+struct GuardError(Option<ApiError>);
+
+fn guard_error(request: &Request) -> Option<ApiError> {
+    request.local_cache(GuardError::default).0.clone()
+}
+
+impl From<Error> for ApiError {
+    fn from(value: Error) -> Self {
+        match value {
+            Error::NotFound => Self::not_found("resource not found"),
+            Error::DatabaseError(kind @ (DatabaseErrorKind::UniqueViolation | DatabaseErrorKind::ForeignKeyViolation), info) => {
+                let (status, code) = match kind {
+                    DatabaseErrorKind::UniqueViolation => (Status::Conflict, "unique_violation"),
+                    _ => (Status::UnprocessableEntity, "foreign_key_violation"),
+                };
+
+                let error = Self::new(status, code, info.message());
+                match info.details() {
+                    Some(detail) => error.with_details(detail),
+                    None => error,
+                }
+            },
+            err => {
+                rocket::error!("database error: {err:?}");
+                Self::internal()
+            },
+        }
+    }
+}
+
+impl From<Debug<Error>> for ApiError {
+    fn from(value: Debug<Error>) -> Self {
+        value.0.into()
+    }
+}
+
+impl From<ValidationErrors> for ApiError {
+    fn from(value: ValidationErrors) -> Self {
+        Self {
+            fields: value.fields,
+            ..Self::new(Status::UnprocessableEntity, "validation_failed", "request failed validation")
+        }
+    }
+}
+
+impl From<CompatibilityReport> for ApiError {
+    fn from(value: CompatibilityReport) -> Self {
+        let fields = value.incompatibilities
+            .iter()
+            .map(|incompatibility| FieldError {
+                field: "components".to_owned(),
+                message: incompatibility.message.clone(),
+            })
+            .collect();
+
+        Self {
+            fields,
+            ..Self::new(Status::UnprocessableEntity, "incompatible_components", "the components are not compatible with each other")
+                .with_details(value.incompatibilities)
+        }
+    }
+}
+
+impl From<uuid::Error> for ApiError {
+    fn from(value: uuid::Error) -> Self {
+        Self::new(Status::BadRequest, "invalid_id", format!("{value}"))
+    }
+}
+
+impl<'r> Responder<'r, 'static> for ApiError {
+    fn respond_to(self, request: &'r Request<'_>) -> response::Result<'static> {
+        (self.status, Json(self)).respond_to(request)
+    }
+}
+
+pub fn stage() -> AdHoc {
+    AdHoc::on_ignite("Error Catchers", |rocket| async {
+        rocket.register("/", catchers![bad_request, unauthorized, forbidden, not_found, unprocessable_entity, internal_error, default])
+    })
+}
+
+#[catch(400)]
+fn bad_request() -> ApiError {
+    ApiError::bad_request("the request could not be understood")
+}
+
+#[catch(401)]
+fn unauthorized(request: &Request) -> ApiError {
+    guard_error(request).unwrap_or_else(|| ApiError::unauthorized("authentication is required"))
+}
+
+#[catch(403)]
+fn forbidden(request: &Request) -> ApiError {
+    guard_error(request).unwrap_or_else(|| ApiError::forbidden("not allowed"))
+}
+
+#[catch(404)]
+fn not_found(request: &Request) -> ApiError {
+    ApiError::not_found(format!("no route matches {}", request.uri()))
+}
+
+#[catch(422)]
+fn unprocessable_entity() -> ApiError {
+    ApiError::new(Status::UnprocessableEntity, "unprocessable_entity", "the request body could not be processed")
+}
+
+#[catch(500)]
+fn internal_error() -> ApiError {
+    ApiError::internal()
+}
+
+#[catch(default)]
+fn default(status: Status, request: &Request) -> ApiError {
+    guard_error(request).unwrap_or_else(|| ApiError::new(status, "http_error", status.reason_lossy()))
+}
@@ -0,0 +1,145 @@
+//! Encoding exports one record at a time, so they can be streamed to the
+//! client while they're read from the database.
+
+use api_types::export::ExportFormat;
+use rocket::{
+    form,
+    futures::{
+        stream::{self, BoxStream},
+        Stream, StreamExt,
+    },
+    http::{ContentType, Status},
+    serde::json::serde_json,
+};
+use rocket_db_pools::diesel::result::Error;
+use serde::Serialize;
+
+use crate::{error::ApiError, validation::ValidationErrors};
+
+/// Reading an [`ExportFormat`] from a query and answering in it
+pub trait ExportFormatExt: Sized {
+    /// The `format` query parameter, JSON when it's missing
+    fn from_query(format: form::Result<'_, Self>) -> Result<Self, ValidationErrors>;
+
+    fn content_type(self) -> ContentType;
+}
+
+impl ExportFormatExt for ExportFormat {
+    fn from_query(format: form::Result<'_, Self>) -> Result<Self, ValidationErrors> {
+        match format {
+            Ok(format) => Ok(format),
+            Err(errors) if errors.iter().all(|error| matches!(error.kind, form::error::ErrorKind::Missing)) => Ok(Self::Json),
+            Err(errors) => Err(errors.into()),
+        }
+    }
+
+    fn content_type(self) -> ContentType {
+        match self {
+            Self::Csv => ContentType::CSV,
+            Self::Json => ContentType::JSON,
+            Self::Ndjson => ContentType::new("application", "x-ndjson"),
+        }
+    }
+}
+
+/// Reads the first of `rows`, which is when their query runs, so an export
+/// failing from the start is answered with an error rather than an empty 200
+pub async fn opened<T: Send + 'static>(mut rows: BoxStream<'static, Result<T, Error>>) -> Result<impl Stream<Item = Result<T, Error>> + Send, Error> {
+    let first = rows.next().await.transpose()?;
+
+    Ok(stream::iter(first.map(Ok)).chain(rows))
+}
+
+/// Writes the pieces of an export: [`Encoder::open`], then a piece per
+/// record, then [`Encoder::close`], or [`Encoder::fail`] when it's cut short
+pub struct Encoder {
+    format: ExportFormat,
+    written: usize,
+}
+
+impl Encoder {
+    pub fn new(format: ExportFormat) -> Self {
+        Self {
+            format,
+            written: 0,
+        }
+    }
+
+    pub fn format(&self) -> ExportFormat {
+        self.format
+    }
+
+    /// Opens the export, whose CSV records are `R`s. CSV starts with the
+    /// header line, so an export of no records still names its columns.
+    pub fn open<R: Serialize + Default>(&self) -> Result<String, csv::Error> {
+        match self.format {
+            ExportFormat::Json => Ok("[".to_owned()),
+            ExportFormat::Csv => csv_header::<R>(),
+            ExportFormat::Ndjson => Ok(String::new()),
+        }
+    }
+
+    pub fn close(&self) -> String {
+        match self.format {
+            ExportFormat::Json => "]".to_owned(),
+            ExportFormat::Csv | ExportFormat::Ndjson => String::new(),
+        }
+    }
+
+    /// Ends an export cut short by an error, as the 200 was already sent.
+    /// JSON is left without its closing bracket, so it doesn't parse; NDJSON
+    /// gets a last line with an [`ApiError`], and CSV a line starting with
+    /// `#`, which imports reject.
+    pub fn fail(&self) -> String {
+        let error = ApiError::new(Status::InternalServerError, "export_failed", "the export was cut short by an error");
+
+        match self.format {
+            ExportFormat::Json => String::new(),
+            ExportFormat::Ndjson => format!("{}\n", serde_json::to_string(&error).unwrap_or_default()),
+            ExportFormat::Csv => format!("# {}: {}\n", error.code, error.message),
+        }
+    }
+
+    /// A CSV line
+    pub fn csv_record(&mut self, record: &impl Serialize) -> Result<String, csv::Error> {
+        let line = csv_lines(record, false)?;
+        self.written += 1;
+
+        Ok(line)
+    }
+
+    /// An element of the JSON array, or a line of NDJSON
+    pub fn json_record(&mut self, record: &impl Serialize) -> Result<String, serde_json::Error> {
+        let json = serde_json::to_string(record)?;
+        let piece = match (self.format, self.written) {
+            (ExportFormat::Ndjson, _) => format!("{json}\n"),
+            (_, 0) => json,
+            _ => format!(",{json}"),
+        };
+        self.written += 1;
+
+        Ok(piece)
+    }
+}
+
+/// `record` as a CSV line, preceded by the header line when `headers`
+fn csv_lines(record: &impl Serialize, headers: bool) -> Result<String, csv::Error> {
+    let mut writer = csv::WriterBuilder::new()
+        .has_headers(headers)
+        .from_writer(Vec::new());
+    writer.serialize(record)?;
+
+    let lines = writer
+        .into_inner()
+        .map_err(|error| error.into_error())?;
+
+    Ok(String::from_utf8_lossy(&lines).into_owned())
+}
+
+/// The header line of `R` records, named by their fields as `csv` names them
+fn csv_header<R: Serialize + Default>() -> Result<String, csv::Error> {
+    let lines = csv_lines(&R::default(), true)?;
+    let end = lines.find('\n').map_or(lines.len(), |end| end + 1);
+
+    Ok(lines[..end].to_owned())
+}
@@ -0,0 +1,172 @@
+//! Reading component catalogs sent to `POST /components/import`.
+//!
+//! Rows are parsed one by one so a malformed row only rejects itself; the
+//! import fails as a whole only when the body can't be read at all.
+
+use api_types::{component::{Component, CreateComponentData}, money::Money};
+use rocket::serde::json::serde_json::{self, Value};
+use serde::{de::{value::StrDeserializer, IntoDeserializer}, Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{error::ApiError, validation::ValidationErrors};
+
+#[derive(Clone, Copy)]
+pub enum ImportFormat {
+    Csv,
+    Json,
+}
+
+impl ImportFormat {
+    /// Renames a validation error's field after the column it came from
+    pub fn field_name(self, field: String) -> String {
+        match (self, field.as_str()) {
+            (Self::Csv, "price.amount") => "price_cents".to_owned(),
+            (Self::Csv, "price.currency") => "currency".to_owned(),
+            _ => field,
+        }
+    }
+}
+
+/// A CSV record, also the shape components are exported in. Prices are in
+/// cents and lists are separated by `;`, e.g.
+/// `AMD,Ryzen 5 7600,cpu,129999,BRL,AM5,ddr5,`
+#[derive(Serialize, Deserialize, Default)]
+pub struct CsvComponent {
+    /// Only written by exports; imports match components by manufacturer
+    /// and model
+    #[serde(default, skip_deserializing)]
+    pub id: Option<Uuid>,
+    pub manufacturer: String,
+    pub model: String,
+    pub slot: String,
+    pub price_cents: i64,
+    pub currency: String,
+    #[serde(default)]
+    pub socket: Option<String>,
+    #[serde(default)]
+    pub memory_generations: String,
+    #[serde(default)]
+    pub interfaces: String,
+}
+
+/// Joins items with `;` under the names the JSON API uses
+fn join_list<T: Serialize>(items: &[T]) -> String {
+    items
+        .iter()
+        .filter_map(|item| match serde_json::to_value(item) {
+            Ok(Value::String(name)) => Some(name),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
+impl From<Component> for CsvComponent {
+    fn from(value: Component) -> Self {
+        let Component { id, manufacturer, model, slot, price, socket, memory_generations, interfaces } = value;
+
+        Self {
+            id: Some(id),
+            manufacturer,
+            model,
+            slot,
+            price_cents: price.amount,
+            currency: price.currency,
+            socket,
+            memory_generations: join_list(&memory_generations),
+            interfaces: join_list(&interfaces),
+        }
+    }
+}
+
+/// Parses every `;` separated item as one of the names the JSON API uses
+fn parse_list<'de, T: Deserialize<'de>>(list: &'de str, field: &str, errors: &mut ValidationErrors) -> Vec<T> {
+    list.split(';')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .filter_map(|item| {
+            let deserializer: StrDeserializer<'_, serde::de::value::Error> = item.into_deserializer();
+            T::deserialize(deserializer)
+                .map_err(|error| errors.add(field, error.to_string()))
+                .ok()
+        })
+        .collect()
+}
+
+impl CsvComponent {
+    fn into_component(self) -> Result<CreateComponentData, ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        let memory_generations = parse_list(&self.memory_generations, "memory_generations", &mut errors);
+        let interfaces = parse_list(&self.interfaces, "interfaces", &mut errors);
+        errors.into_result()?;
+
+        Ok(CreateComponentData {
+            manufacturer: self.manufacturer,
+            model: self.model,
+            slot: self.slot,
+            price: Money::new(self.price_cents, self.currency),
+            socket: self.socket.filter(|socket| !socket.trim().is_empty()),
+            memory_generations,
+            interfaces,
+        })
+    }
+}
+
+fn parse_csv(body: &str) -> Result<Vec<Result<CreateComponentData, ValidationErrors>>, ApiError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(body.as_bytes());
+
+    let headers = reader
+        .headers()
+        .map_err(|error| ApiError::bad_request(format!("invalid CSV header: {error}")))?
+        .clone();
+
+    let rows = reader
+        .deserialize::<CsvComponent>()
+        .map(|record| match record {
+            Ok(record) => record.into_component(),
+            Err(error) => {
+                let mut errors = ValidationErrors::default();
+                match error.kind() {
+                    csv::ErrorKind::Deserialize { err, .. } => {
+                        let field = err.field()
+                            .and_then(|index| headers.get(index as usize))
+                            .unwrap_or("component");
+                        errors.add(field, err.kind().to_string());
+                    },
+                    _ => errors.add("component", error.to_string()),
+                }
+                Err(errors)
+            },
+        })
+        .collect();
+
+    Ok(rows)
+}
+
+fn parse_json(body: &str) -> Result<Vec<Result<CreateComponentData, ValidationErrors>>, ApiError> {
+    let values: Vec<Value> = serde_json::from_str(body)
+        .map_err(|error| ApiError::bad_request(format!("expected a JSON array of components: {error}")))?;
+
+    let rows = values
+        .into_iter()
+        .map(|value| {
+            serde_json::from_value(value).map_err(|error| {
+                let mut errors = ValidationErrors::default();
+                errors.add("component", error.to_string());
+                errors
+            })
+        })
+        .collect();
+
+    Ok(rows)
+}
+
+/// Every row of the catalog, in order, parsed or with why it couldn't be
+pub fn parse(format: ImportFormat, body: &str) -> Result<Vec<Result<CreateComponentData, ValidationErrors>>, ApiError> {
+    match format {
+        ImportFormat::Csv => parse_csv(body),
+        ImportFormat::Json => parse_json(body),
+    }
+}
@@ -1,19 +1,45 @@
 use database::Db;
-use rocket::launch;
+use rocket::{Build, Rocket};
 use rocket_db_pools::Database;
 
 pub mod dto;
 pub mod repository;
 pub mod api;
-pub mod schema;
-pub mod types;
+pub mod auth;
+pub mod compatibility;
 pub mod models;
+pub mod money;
 pub mod database;
+pub mod error;
+pub mod export;
+pub mod import;
+pub mod migrations;
+pub mod openapi;
+pub mod validation;
 
-#[launch]
-fn rocket() -> _ {
+/// `--migrate-only` applies the pending migrations and exits. Like with
+/// `#[launch]`, rocket's errors report themselves when dropped.
+#[rocket::main]
+async fn main() {
+    if std::env::args().any(|arg| arg == "--migrate-only") {
+        let _ = migrations::migrate_only().await;
+    } else {
+        let _ = rocket().launch().await;
+    }
+}
+
+fn rocket() -> Rocket<Build> {
     rocket::build()
         .attach(Db::init())
+        .attach(migrations::stage())
+        .attach(repository::stage())
+        .attach(error::stage())
+        .attach(auth::stage())
         .attach(api::component::stage())
+        .attach(api::currency::stage())
+        .attach(api::revision::stage())
         .attach(api::rig::stage())
+        .attach(api::slot::stage())
+        .attach(api::user::stage())
+        .attach(openapi::stage())
 }
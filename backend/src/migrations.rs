@@ -0,0 +1,104 @@
+//! The `migrations` directory, embedded into the binary and applied on
+//! launch, so deployments don't need `diesel migration run` beforehand.
+
+use std::collections::HashSet;
+
+use diesel::{migration::MigrationSource, pg::Pg, Connection};
+use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
+use rocket::{fairing::AdHoc, tokio::task, Build, Config, Rocket};
+use rocket_db_pools::{
+    diesel::{async_connection_wrapper::AsyncConnectionWrapper, AsyncPgConnection},
+    Database,
+};
+
+use crate::database::Db;
+
+pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations");
+
+/// Applies the pending migrations on launch, before anything else reaches the
+/// database. Refuses to launch when the database has migrations this build
+/// doesn't know about, i.e. a newer backend already migrated it.
+///
+/// Disabled with `run_migrations = false`, e.g. in `Rocket.toml` or as
+/// `ROCKET_RUN_MIGRATIONS=false`, for deployments that migrate separately.
+pub fn stage() -> AdHoc {
+    AdHoc::try_on_ignite("Migrations", |rocket| async {
+        if rocket.figment().find_value("run_migrations").is_ok() {
+            match rocket.figment().extract_inner::<bool>("run_migrations") {
+                Ok(true) => {},
+                Ok(false) => return Ok(rocket),
+                Err(err) => {
+                    rocket::error!("invalid run_migrations config: {err}");
+                    return Err(rocket);
+                },
+            }
+        }
+
+        match run(&rocket).await {
+            Ok(applied) if applied.is_empty() => {
+                rocket::info!("the database is up to date");
+                Ok(rocket)
+            },
+            Ok(applied) => {
+                rocket::info!("applied migrations {}", applied.join(", "));
+                Ok(rocket)
+            },
+            Err(err) => {
+                rocket::error!("could not migrate the database: {err}");
+                Err(rocket)
+            },
+        }
+    })
+}
+
+/// `--migrate-only`: applies the pending migrations and exits without
+/// launching, whatever `run_migrations` says
+pub async fn migrate_only() -> Result<(), rocket::Error> {
+    let figment = Config::figment().merge(("run_migrations", true));
+    rocket::custom(figment).attach(stage()).ignite().await?;
+
+    Ok(())
+}
+
+/// Applies the pending migrations, returning their versions. Connects to
+/// [`Db`]'s database on its own, as the harness needs a synchronous
+/// connection: one blocking on an async connection under it.
+async fn run(rocket: &Rocket<Build>) -> Result<Vec<String>, String> {
+    let url: String = rocket
+        .figment()
+        .extract_inner(&format!("databases.{}.url", Db::NAME))
+        .map_err(|err| err.to_string())?;
+
+    task::spawn_blocking(move || {
+        let mut connection = AsyncConnectionWrapper::<AsyncPgConnection>::establish(&url).map_err(|err| err.to_string())?;
+        ensure_known(&mut connection)?;
+
+        let applied = connection.run_pending_migrations(MIGRATIONS).map_err(|err| err.to_string())?;
+        Ok(applied.iter().map(ToString::to_string).collect())
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Fails when the database has applied migrations missing from [`MIGRATIONS`]
+fn ensure_known(connection: &mut impl MigrationHarness<Pg>) -> Result<(), String> {
+    let known: HashSet<_> = MigrationSource::<Pg>::migrations(&MIGRATIONS)
+        .map_err(|err| err.to_string())?
+        .iter()
+        .map(|migration| migration.name().version().as_owned())
+        .collect();
+
+    let unknown: Vec<String> = connection
+        .applied_migrations()
+        .map_err(|err| err.to_string())?
+        .into_iter()
+        .filter(|version| !known.contains(version))
+        .map(|version| version.to_string())
+        .collect();
+
+    if unknown.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("the database is ahead of this build, which doesn't know its migrations {}", unknown.join(", ")))
+    }
+}
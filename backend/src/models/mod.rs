@@ -1,2 +1,4 @@
 pub mod component;
+pub mod revision;
 pub mod rig;
+pub mod user;
@@ -0,0 +1,56 @@
+use api_types::{revision::RevisionComponent, rig::Rig};
+use rocket_db_pools::diesel::{self, Identifiable, Insertable, Queryable, Selectable, Associations};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::{
+    error::ApiResult,
+    money::{Convert, Converter},
+};
+
+/// An immutable snapshot of a rig, taken every time its name or
+/// components change. Numbers count up from 1 for every rig.
+#[derive(
+    Serialize,
+    Deserialize,
+    ToSchema,
+    Debug,
+    Clone,
+    Queryable,
+    Insertable,
+    Identifiable,
+    Selectable,
+    Associations,
+)]
+#[diesel(belongs_to(Rig))]
+#[diesel(primary_key(rig_id, number))]
+#[diesel(table_name = api_types::schema::rig_revisions)]
+pub struct RigRevision {
+    pub rig_id: Uuid,
+    pub number: i32,
+    pub name: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+impl RigRevision {
+    pub fn new(rig_id: Uuid, number: i32, name: String) -> Self {
+        Self {
+            rig_id,
+            number,
+            name,
+            created_at: OffsetDateTime::now_utc(),
+        }
+    }
+}
+
+impl Convert for RevisionComponent {
+    fn converted(self, converter: &Converter) -> ApiResult<Self> {
+        Ok(Self {
+            price: converter.convert(&self.price)?,
+            ..self
+        })
+    }
+}
@@ -1,40 +1,34 @@
-use rocket_db_pools::diesel::{self, AsChangeset, Identifiable, Insertable, Queryable, Selectable, Associations};
+use api_types::{component::Component, rig::{Rig, RigComponentData}};
+use rand_core::{OsRng, RngCore};
+use rocket_db_pools::diesel::{self, Identifiable, Insertable, Queryable, Selectable, Associations};
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use uuid::Uuid;
 
-use crate::models::component::Component;
-
 // use crate::schema::{rigs, rig_components};
 
-#[derive(
-    Serialize,
-    Deserialize,
-    Debug,
-    Clone,
-    Queryable,
-    Insertable,
-    Identifiable,
-    Selectable,
-    AsChangeset,
-)]
-#[diesel(table_name = crate::schema::rigs)]
-pub struct Rig {
-    pub id: Uuid,
-    pub name: String,
-}
+const SHARE_SLUG_ALPHABET: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const SHARE_SLUG_LENGTH: usize = 12;
 
-impl Rig {
-    pub fn new(name: String) -> Self {
-        Self {
-            id: Uuid::new_v4(),
-            name,
-        }
-    }
+/// A random slug, unguessable enough to stand in for an account
+pub fn generate_share_slug() -> String {
+    (0..SHARE_SLUG_LENGTH)
+        .map(|_| {
+            // 62 * 4 = 248, so taking bytes below it keeps every character equally likely
+            loop {
+                let byte = (OsRng.next_u32() & 0xff) as usize;
+                if byte < SHARE_SLUG_ALPHABET.len() * 4 {
+                    break SHARE_SLUG_ALPHABET[byte % SHARE_SLUG_ALPHABET.len()] as char;
+                }
+            }
+        })
+        .collect()
 }
 
 #[derive(
     Serialize,
     Deserialize,
+    ToSchema,
     Debug,
     Clone,
     Queryable,
@@ -46,8 +40,23 @@ impl Rig {
 #[diesel(belongs_to(Rig))]
 #[diesel(belongs_to(Component))]
 #[diesel(primary_key(rig_id, component_id))]
-#[diesel(table_name = crate::schema::rig_components)]
+#[diesel(table_name = api_types::schema::rig_components)]
 pub struct RigComponent {
     pub rig_id: Uuid,
     pub component_id: Uuid,
+    pub quantity: i32,
+}
+
+impl RigComponent {
+    pub fn new(rig_id: Uuid, data: RigComponentData) -> Self {
+        let RigComponentData { component_id, quantity } = data;
+        Self { rig_id, component_id, quantity }
+    }
+
+    pub fn for_rig(rig_id: Uuid, components: Vec<RigComponentData>) -> Vec<Self> {
+        components
+            .into_iter()
+            .map(|component| Self::new(rig_id, component))
+            .collect()
+    }
 }
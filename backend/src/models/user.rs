@@ -0,0 +1,24 @@
+use api_types::user::User;
+use rocket_db_pools::diesel::{self, Identifiable, Insertable, Queryable, Selectable, Associations};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+#[derive(
+    Debug,
+    Clone,
+    Queryable,
+    Insertable,
+    Identifiable,
+    Selectable,
+    Associations,
+)]
+#[diesel(belongs_to(User))]
+#[diesel(primary_key(token_hash))]
+#[diesel(table_name = api_types::schema::sessions)]
+pub struct Session {
+    /// SHA-256 of the session token, hex encoded
+    pub token_hash: String,
+    pub user_id: Uuid,
+    pub created_at: OffsetDateTime,
+    pub expires_at: OffsetDateTime,
+}
@@ -0,0 +1,88 @@
+use std::collections::HashMap;
+
+use api_types::{currency::ExchangeRate, money::Money};
+use rocket::http::Status;
+
+use crate::error::{ApiError, ApiResult};
+
+/// Currency every exchange rate is expressed in
+pub const BASE_CURRENCY: &str = "BRL";
+
+/// Rates are stored as millionths of the base currency
+pub const RATE_SCALE: i64 = 1_000_000;
+
+/// Divides rounding half to even ("banker's rounding"), so converting many
+/// prices doesn't drift the sum up or down.
+fn div_round_half_even(numerator: i128, denominator: i128) -> i128 {
+    let quotient = numerator.div_euclid(denominator);
+    let remainder = numerator.rem_euclid(denominator);
+
+    match (2 * remainder).cmp(&denominator) {
+        std::cmp::Ordering::Less => quotient,
+        std::cmp::Ordering::Greater => quotient + 1,
+        std::cmp::Ordering::Equal => quotient + quotient.rem_euclid(2),
+    }
+}
+
+/// Converts amounts into one currency using the stored exchange rates.
+///
+/// Each amount is converted on its own and rounded half to even to the
+/// target currency's cents; totals are then summed from converted amounts so
+/// they always match their lines.
+pub struct Converter {
+    to: String,
+    to_rate: i64,
+    rates: HashMap<String, i64>,
+}
+
+impl Converter {
+    /// `None` when there's no rate for `to`
+    pub fn new(to: &str, rates: Vec<ExchangeRate>) -> Option<Self> {
+        let rates: HashMap<String, i64> = rates
+            .into_iter()
+            .map(|rate| (rate.currency, rate.rate_micros))
+            .collect();
+
+        let to_rate = *rates.get(to)?;
+
+        Some(Self {
+            to: to.to_owned(),
+            to_rate,
+            rates,
+        })
+    }
+
+    pub fn currency(&self) -> &str {
+        &self.to
+    }
+
+    /// Rate of the target currency
+    pub fn rate(&self) -> i64 {
+        self.to_rate
+    }
+
+    /// Fails with a 500 naming the currency if `money` is in one without a
+    /// rate, which the foreign keys on prices should rule out
+    pub fn convert(&self, money: &Money) -> ApiResult<Money> {
+        if money.currency == self.to {
+            return Ok(money.clone());
+        }
+
+        let Some(&from) = self.rates.get(&money.currency) else {
+            rocket::error!("cannot convert a price in {}: it has no exchange rate", money.currency);
+            return Err(ApiError::new(Status::InternalServerError, "internal_error", format!("no exchange rate for {}", money.currency)));
+        };
+        let amount = div_round_half_even(i128::from(money.amount) * i128::from(from), i128::from(self.to_rate));
+
+        // Saturates rather than wrapping for prices too large to convert
+        let amount = amount.clamp(i64::MIN.into(), i64::MAX.into()) as i64;
+
+        Ok(Money::new(amount, self.to.clone()))
+    }
+}
+
+/// Something priced, which can be repriced in another currency
+pub trait Convert: Sized {
+    /// The same thing with its prices in the converter's currency
+    fn converted(self, converter: &Converter) -> ApiResult<Self>;
+}
@@ -0,0 +1,150 @@
+//! OpenAPI document of the API, generated from the routes' `#[utoipa::path]`
+//! attributes and the schemas they use.
+
+use api_types::{component::ComponentSort, export::ExportFormat};
+use rocket::fairing::AdHoc;
+use utoipa::{
+    openapi::security::{Http, HttpAuthScheme, SecurityScheme},
+    Modify, OpenApi,
+};
+use utoipa_swagger_ui::SwaggerUi;
+
+use crate::api;
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Rigs API", description = "Components, rigs built out of them and their prices in any currency."),
+    paths(
+        api::component::detail,
+        api::component::prices,
+        api::component::list,
+        api::component::search,
+        api::component::export,
+        api::component::create,
+        api::component::import,
+        api::component::update,
+        api::component::patch,
+        api::component::delete,
+        api::currency::list,
+        api::currency::detail,
+        api::currency::update,
+        api::revision::list,
+        api::revision::detail,
+        api::revision::diff,
+        api::revision::restore,
+        api::rig::list,
+        api::rig::export,
+        api::rig::detail,
+        api::rig::valuation,
+        api::rig::create,
+        api::rig::check_compatibility,
+        api::rig::rename,
+        api::rig::update_components,
+        api::rig::clone,
+        api::rig::share,
+        api::rig::shared,
+        api::rig::delete,
+        api::slot::list,
+        api::slot::detail,
+        api::slot::create,
+        api::slot::update,
+        api::slot::delete,
+        api::user::register,
+        api::user::login,
+        api::user::logout,
+        api::user::me,
+        api::user::list,
+        api::user::update_role,
+    ),
+    // Only referenced by query parameters, which don't register their schemas
+    components(schemas(ComponentSort, ExportFormat)),
+    modifiers(&BearerAuth),
+)]
+pub struct ApiDoc;
+
+/// Session tokens, sent as `Authorization: Bearer <token>`
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        openapi
+            .components
+            .get_or_insert_with(Default::default)
+            .add_security_scheme("bearer_auth", SecurityScheme::Http(Http::new(HttpAuthScheme::Bearer)));
+    }
+}
+
+/// Serves the document at `/openapi.json` and Swagger UI, bundled in the
+/// binary, at `/docs`
+pub fn stage() -> AdHoc {
+    AdHoc::on_ignite("OpenAPI Docs", |rocket| async {
+        rocket.mount("/", SwaggerUi::new("/docs/<_..>").url("/openapi.json", ApiDoc::openapi()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use utoipa::OpenApi;
+
+    use super::ApiDoc;
+
+    /// `/rigs/<id>/revisions?<currency>` as `/rigs/{id}/revisions`
+    fn openapi_path(path: &str) -> String {
+        let path = path
+            .split('/')
+            .map(|segment| match segment.strip_prefix('<').and_then(|segment| segment.strip_suffix('>')) {
+                Some(param) => format!("{{{param}}}"),
+                None => segment.to_owned(),
+            })
+            .collect::<Vec<_>>()
+            .join("/");
+
+        match path.trim_end_matches('/') {
+            "" => "/".to_owned(),
+            path => path.to_owned(),
+        }
+    }
+
+    #[rocket::async_test]
+    async fn spec_matches_routes() {
+        // Ignites without a database
+        let rocket = crate::rocket();
+        let figment = rocket.figment().clone().merge(("run_migrations", false));
+        let rocket = rocket.configure(figment).ignite().await.expect("rocket ignites");
+
+        let routes: BTreeSet<(String, String)> = rocket
+            .routes()
+            .map(|route| (route.method.as_str().to_lowercase(), openapi_path(route.uri.path())))
+            // The document and its UI
+            .filter(|(_, path)| !path.starts_with("/docs") && path != "/openapi.json")
+            .collect();
+
+        let spec = ApiDoc::openapi();
+        let documented: BTreeSet<(String, String)> = spec
+            .paths
+            .paths
+            .iter()
+            .flat_map(|(path, item)| {
+                [
+                    ("get", item.get.is_some()),
+                    ("put", item.put.is_some()),
+                    ("post", item.post.is_some()),
+                    ("delete", item.delete.is_some()),
+                    ("patch", item.patch.is_some()),
+                    ("head", item.head.is_some()),
+                    ("options", item.options.is_some()),
+                ]
+                .into_iter()
+                .filter(|(_, present)| *present)
+                .map(|(method, _)| (method.to_owned(), openapi_path(path)))
+            })
+            .collect();
+
+        let undocumented: Vec<_> = routes.difference(&documented).collect();
+        let stale: Vec<_> = documented.difference(&routes).collect();
+        assert!(undocumented.is_empty(), "routes missing from the OpenAPI document: {undocumented:?}");
+        assert!(stale.is_empty(), "OpenAPI paths without a route: {stale:?}");
+    }
+}
@@ -1,43 +1,450 @@
-use rocket_db_pools::{
-    diesel::{prelude::RunQueryDsl, QueryResult, QueryDsl, ExpressionMethods},
-    Connection,
+use std::collections::HashMap;
+
+use api_types::{
+    component::{Component, ComponentPrice, ComponentQuery, ComponentSort},
+    currency::ExchangeRate,
+    page::Page,
+    rig::Rig,
+    schema::{component_prices, components, exchange_rates, rigs, rig_components},
+    slot::Slot,
+};
+use rocket_db_pools::diesel::{
+    prelude::RunQueryDsl, result::Error, scoped_futures::ScopedFutureExt, upsert::excluded, AsyncConnection, AsyncPgConnection,
+    BelongingToDsl, PgTextExpressionMethods, QueryResult, QueryDsl, ExpressionMethods, SelectableHelper,
+};
+use diesel::{
+    helper_types::{InnerJoin, InnerJoinQuerySource, IntoBoxed},
+    pg::Pg,
+    sql_function,
+    sql_types::{BigInt, Numeric, Text},
+    BoxableExpression,
+};
+use diesel_full_text_search::{configuration::TsConfigurationByName, to_tsquery_with_search_config, ts_rank, TsVectorExtensions};
+use rocket::{
+    futures::{stream::BoxStream, Stream, StreamExt},
+    response::stream::stream,
 };
 use uuid::Uuid;
 
-use crate::{database::Db, models::component::Component};
-use crate::schema::components;
+use crate::{dto::component::ComponentChangeset, validation::rig::Catalog};
+
+use super::{currency, slot, DieselRepository};
+
+/// Where the routes read and write components, along with the slots and
+/// exchange rates components are checked and priced against
+#[rocket::async_trait]
+pub trait ComponentRepository: Send + Sync {
+    async fn get_component(&self, id: Uuid) -> QueryResult<Component>;
+
+    /// Every component, ordered by manufacturer and model, read as the
+    /// stream is consumed
+    async fn stream_components(&self) -> QueryResult<BoxStream<'static, Result<Component, Error>>>;
+
+    /// A page of the components matching `query`. `rate` is the one of the
+    /// currency its price bounds are in.
+    async fn list_components(&self, query: &ComponentQuery, rate: i64) -> QueryResult<Page<Component>>;
+
+    /// Components whose manufacturer and model have a word starting with
+    /// each of the words of `terms`, best matches first
+    async fn search_components(&self, terms: &str, limit: i64) -> QueryResult<Vec<Component>>;
+
+    async fn list_components_by_id(&self, ids: &[Uuid]) -> QueryResult<Vec<Component>>;
+
+    /// Ids of the components matching any of the `(manufacturer, model)` pairs
+    async fn find_component_ids(&self, names: &[(String, String)]) -> QueryResult<HashMap<(String, String), Uuid>>;
+
+    async fn create_component(&self, component: Component) -> QueryResult<Component>;
+
+    async fn update_component(&self, component: Component) -> QueryResult<Component>;
+
+    async fn patch_component(&self, id: Uuid, changes: ComponentChangeset) -> QueryResult<Component>;
+
+    /// Creates every component, or updates the one with its manufacturer and
+    /// model, all together or none of them if any fails. Returns them as
+    /// stored, so the updated ones have their existing ids.
+    async fn import_components(&self, components: Vec<Component>) -> QueryResult<Vec<Component>>;
+
+    /// Deletes the component unless rigs use it, returning those rigs
+    /// instead. The component stays locked from the check to the delete, so
+    /// no rig can take it in between.
+    async fn delete_component(&self, id: Uuid) -> QueryResult<Vec<Rig>>;
+
+    /// Every price the component had, oldest first
+    async fn list_component_prices(&self, id: Uuid) -> QueryResult<Vec<ComponentPrice>>;
+
+    async fn list_slots(&self) -> QueryResult<Vec<Slot>>;
+
+    async fn list_rates(&self) -> QueryResult<Vec<ExchangeRate>>;
+}
+
+#[rocket::async_trait]
+impl ComponentRepository for DieselRepository {
+    async fn get_component(&self, id: Uuid) -> QueryResult<Component> {
+        get_component(id, &mut *self.connection().await?).await
+    }
+
+    async fn stream_components(&self) -> QueryResult<BoxStream<'static, Result<Component, Error>>> {
+        let mut db = self.connection().await?;
+
+        Ok(stream! {
+            let mut components = match stream_components(&mut db).await {
+                Ok(components) => components,
+                Err(err) => {
+                    yield Err(err.0);
+                    return;
+                },
+            };
+
+            while let Some(component) = components.next().await {
+                yield component;
+            }
+        }.boxed())
+    }
+
+    async fn list_components(&self, query: &ComponentQuery, rate: i64) -> QueryResult<Page<Component>> {
+        list_components(query, rate, &mut *self.connection().await?).await
+    }
+
+    async fn search_components(&self, terms: &str, limit: i64) -> QueryResult<Vec<Component>> {
+        search_components(terms, limit, &mut *self.connection().await?).await
+    }
+
+    async fn list_components_by_id(&self, ids: &[Uuid]) -> QueryResult<Vec<Component>> {
+        list_components_by_id(ids, &mut *self.connection().await?).await
+    }
+
+    async fn find_component_ids(&self, names: &[(String, String)]) -> QueryResult<HashMap<(String, String), Uuid>> {
+        find_component_ids(names, &mut *self.connection().await?).await
+    }
 
-pub async fn get_component(id: Uuid, db: &mut Connection<Db>) -> QueryResult<Component> {
+    async fn create_component(&self, component: Component) -> QueryResult<Component> {
+        create_component(component, &mut *self.connection().await?).await
+    }
+
+    async fn update_component(&self, component: Component) -> QueryResult<Component> {
+        update_component(component, &mut *self.connection().await?).await
+    }
+
+    async fn patch_component(&self, id: Uuid, changes: ComponentChangeset) -> QueryResult<Component> {
+        patch_component(id, changes, &mut *self.connection().await?).await
+    }
+
+    async fn import_components(&self, components: Vec<Component>) -> QueryResult<Vec<Component>> {
+        self.connection().await?.transaction(|db| async move {
+            let mut stored = Vec::with_capacity(components.len());
+            for component in components {
+                stored.push(upsert_component(component, db).await?);
+            }
+            QueryResult::Ok(stored)
+        }.scope_boxed()).await
+    }
+
+    async fn delete_component(&self, id: Uuid) -> QueryResult<Vec<Rig>> {
+        self.connection().await?.transaction(|db| async move {
+            // Rig components hold a key share lock on the component they
+            // point to, which waits for this one
+            lock_component(id, db).await?;
+
+            let rigs = list_rigs_using_component(id, db).await?;
+            if rigs.is_empty() {
+                delete_component(id, db).await?;
+            }
+            QueryResult::Ok(rigs)
+        }.scope_boxed()).await
+    }
+
+    async fn list_component_prices(&self, id: Uuid) -> QueryResult<Vec<ComponentPrice>> {
+        list_component_prices(id, &mut *self.connection().await?).await
+    }
+
+    async fn list_slots(&self) -> QueryResult<Vec<Slot>> {
+        slot::list_slots(&mut *self.connection().await?).await
+    }
+
+    async fn list_rates(&self) -> QueryResult<Vec<ExchangeRate>> {
+        currency::list_rates(&mut *self.connection().await?).await
+    }
+}
+
+async fn get_component(id: Uuid, db: &mut AsyncPgConnection) -> QueryResult<Component> {
     Ok(
         components::table
             .find(id)
-            .get_result::<Component>(db)
+            .select(Component::as_select())
+            .get_result(db)
+            .await?
+    )
+}
+
+sql_function!(fn lower(x: Text) -> Text);
+// Plain `numeric(x)` parses as the type, not its cast function
+sql_function! {
+    #[sql_name = "pg_catalog.numeric"]
+    fn numeric(x: BigInt) -> Numeric;
+}
+
+fn escape_like(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+/// A component's price in millionths of the base currency's cents. In
+/// `numeric`, as the product of a price and a rate can overflow `bigint`.
+fn base_price() -> Box<dyn BoxableExpression<InnerJoinQuerySource<components::table, exchange_rates::table>, Pg, SqlType = Numeric>> {
+    Box::new(numeric(components::price_cents) * numeric(exchange_rates::rate_micros))
+}
+
+/// Filters the components, joined with the rate of their currency.
+/// `rate` is the one of the currency the price bounds are in.
+fn filter_components(query: &ComponentQuery, rate: i64) -> IntoBoxed<'_, InnerJoin<components::table, exchange_rates::table>, Pg> {
+    let mut select = components::table
+        .inner_join(exchange_rates::table)
+        .into_boxed();
+
+    if let Some(slot) = &query.slot {
+        select = select.filter(components::slot.eq(slot));
+    }
+    if let Some(manufacturer) = &query.manufacturer {
+        select = select.filter(components::manufacturer.ilike(escape_like(manufacturer)));
+    }
+    if let Some(min_price) = query.min_price {
+        select = select.filter(base_price().ge(numeric(min_price) * numeric(rate)));
+    }
+    if let Some(max_price) = query.max_price {
+        select = select.filter(base_price().le(numeric(max_price) * numeric(rate)));
+    }
+
+    select
+}
+
+/// Every component, read from the database as the stream is consumed
+async fn stream_components(db: &mut AsyncPgConnection) -> QueryResult<impl Stream<Item = Result<Component, Error>> + Send + '_> {
+    Ok(
+        components::table
+            .order((components::manufacturer, components::model, components::id))
+            .select(Component::as_select())
+            .load_stream(db)
             .await?
     )
 }
 
-pub async fn list_components(db: &mut Connection<Db>) -> QueryResult<Vec<Component>> {
+async fn list_components(query: &ComponentQuery, rate: i64, db: &mut AsyncPgConnection) -> QueryResult<Page<Component>> {
+    let total = filter_components(query, rate)
+        .count()
+        .get_result::<i64>(db)
+        .await?;
+
+    let select = match query.sort {
+        None => filter_components(query, rate).order((components::manufacturer, components::model)),
+        Some(ComponentSort::PriceAsc) => filter_components(query, rate).order(base_price().asc()),
+        Some(ComponentSort::PriceDesc) => filter_components(query, rate).order(base_price().desc()),
+        Some(ComponentSort::Model) => filter_components(query, rate).order(components::model),
+    };
+
+    let pagination = query.pagination();
+    let items = select
+        // Ties are broken by id so pages never overlap
+        .then_order_by(components::id)
+        .limit(pagination.per_page)
+        .offset(pagination.offset())
+        .select(Component::as_select())
+        .load(db)
+        .await?;
+
+    Ok(Page::new(items, pagination, total))
+}
+
+/// Turns free text into a `tsquery` where every word must match as a prefix,
+/// e.g. `"rtx 40"` becomes `rtx:* & 40:*`.
+fn prefix_tsquery(terms: &str) -> Option<String> {
+    let words: Vec<String> = terms
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| format!("{}:*", word.to_lowercase()))
+        .collect();
+
+    (!words.is_empty()).then(|| words.join(" & "))
+}
+
+async fn search_components(terms: &str, limit: i64, db: &mut AsyncPgConnection) -> QueryResult<Vec<Component>> {
+    let Some(query) = prefix_tsquery(terms) else {
+        return Ok(Vec::new());
+    };
+
     Ok(
         components::table
-            .load::<Component>(db)
+            .filter(components::search.matches(to_tsquery_with_search_config(TsConfigurationByName("simple"), &query)))
+            .order((
+                // Exact model number hits rank above anything the text search scores
+                lower(components::model).eq(terms.trim().to_lowercase()).desc(),
+                ts_rank(components::search, to_tsquery_with_search_config(TsConfigurationByName("simple"), &query)).desc(),
+                components::model,
+            ))
+            .limit(limit)
+            .select(Component::as_select())
+            .load(db)
             .await?
     )
 }
 
-pub async fn list_components_by_id(ids: &[Uuid], db: &mut Connection<Db>) -> QueryResult<Vec<Component>> {
+async fn list_components_by_id(ids: &[Uuid], db: &mut AsyncPgConnection) -> QueryResult<Vec<Component>> {
     Ok(
         components::table
             .filter(components::id.eq_any(ids))
-            .load::<Component>(db)
+            .select(Component::as_select())
+            .load(db)
+            .await?
+    )
+}
+
+/// Reads the catalog a rig's components are checked against, locking its
+/// rows until the transaction ends so they can't change before the checked
+/// components are written
+pub async fn load_catalog(ids: &[Uuid], db: &mut AsyncPgConnection) -> QueryResult<Catalog> {
+    let components = components::table
+        .filter(components::id.eq_any(ids))
+        .select(Component::as_select())
+        .for_share()
+        .load(db)
+        .await?;
+
+    Ok(Catalog {
+        components,
+        slots: slot::lock_slots(db).await?,
+    })
+}
+
+async fn find_component_ids(names: &[(String, String)], db: &mut AsyncPgConnection) -> QueryResult<HashMap<(String, String), Uuid>> {
+    let manufacturers: Vec<&str> = names.iter().map(|(manufacturer, _)| manufacturer.as_str()).collect();
+    let models: Vec<&str> = names.iter().map(|(_, model)| model.as_str()).collect();
+
+    // Narrowed down in SQL, matched exactly here
+    let found: Vec<(Uuid, String, String)> = components::table
+        .filter(components::manufacturer.eq_any(manufacturers))
+        .filter(components::model.eq_any(models))
+        .select((components::id, components::manufacturer, components::model))
+        .load(db)
+        .await?;
+
+    Ok(found
+        .into_iter()
+        .map(|(id, manufacturer, model)| ((manufacturer, model), id))
+        .collect())
+}
+
+async fn create_component(component: Component, db: &mut AsyncPgConnection) -> QueryResult<Component> {
+    Ok(
+        diesel::insert_into(components::table)
+            .values(component)
+            .returning(Component::as_returning())
+            .get_result(db)
             .await?
     )
 }
 
-pub async fn create_component(component: Component, db: &mut Connection<Db>) -> QueryResult<Component> {
+async fn update_component(component: Component, db: &mut AsyncPgConnection) -> QueryResult<Component> {
+    let Component { id, manufacturer, model, slot, price, socket, memory_generations, interfaces } = component;
+
+    Ok(
+        diesel::update(components::table.find(id))
+            .set((
+                components::manufacturer.eq(manufacturer),
+                components::model.eq(model),
+                components::slot.eq(slot),
+                components::price_cents.eq(price.amount),
+                components::currency.eq(price.currency),
+                components::socket.eq(socket),
+                components::memory_generations.eq(memory_generations),
+                components::interfaces.eq(interfaces),
+            ))
+            .returning(Component::as_returning())
+            .get_result(db)
+            .await?
+    )
+}
+
+/// Inserts `component`, or updates the one with its manufacturer and model
+/// in place, keeping that one's id
+async fn upsert_component(component: Component, db: &mut AsyncPgConnection) -> QueryResult<Component> {
     Ok(
         diesel::insert_into(components::table)
             .values(component)
-            .get_result::<Component>(db)
+            .on_conflict((components::manufacturer, components::model))
+            .do_update()
+            .set((
+                components::slot.eq(excluded(components::slot)),
+                components::price_cents.eq(excluded(components::price_cents)),
+                components::currency.eq(excluded(components::currency)),
+                components::socket.eq(excluded(components::socket)),
+                components::memory_generations.eq(excluded(components::memory_generations)),
+                components::interfaces.eq(excluded(components::interfaces)),
+            ))
+            .returning(Component::as_returning())
+            .get_result(db)
+            .await?
+    )
+}
+
+async fn patch_component(id: Uuid, changes: ComponentChangeset, db: &mut AsyncPgConnection) -> QueryResult<Component> {
+    // Diesel refuses to build an UPDATE without any columns to set
+    if changes.is_empty() {
+        return get_component(id, db).await;
+    }
+
+    Ok(
+        diesel::update(components::table.find(id))
+            .set(changes)
+            .returning(Component::as_returning())
+            .get_result(db)
+            .await?
+    )
+}
+
+async fn lock_component(id: Uuid, db: &mut AsyncPgConnection) -> QueryResult<Uuid> {
+    Ok(
+        components::table
+            .find(id)
+            .select(components::id)
+            .for_update()
+            .get_result(db)
+            .await?
+    )
+}
+
+async fn delete_component(id: Uuid, db: &mut AsyncPgConnection) -> QueryResult<usize> {
+    Ok(
+        diesel::delete(components::table.find(id))
+            .execute(db)
+            .await?
+    )
+}
+
+async fn list_rigs_using_component(id: Uuid, db: &mut AsyncPgConnection) -> QueryResult<Vec<Rig>> {
+    Ok(
+        rigs::table
+            .inner_join(rig_components::table)
+            .filter(rig_components::component_id.eq(id))
+            .select(Rig::as_select())
+            .load::<Rig>(db)
+            .await?
+    )
+}
+
+async fn list_component_prices(id: Uuid, db: &mut AsyncPgConnection) -> QueryResult<Vec<ComponentPrice>> {
+    let component = get_component(id, db).await?;
+
+    Ok(
+        ComponentPrice::belonging_to(&component)
+            .order_by((component_prices::changed_at, component_prices::id))
+            .select((
+                component_prices::id,
+                component_prices::component_id,
+                (component_prices::price_cents, component_prices::currency),
+                component_prices::changed_at,
+            ))
+            .load(db)
             .await?
     )
 }
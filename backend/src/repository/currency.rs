@@ -0,0 +1,64 @@
+use api_types::{currency::ExchangeRate, schema::exchange_rates};
+use rocket_db_pools::diesel::{prelude::RunQueryDsl, upsert::excluded, AsyncPgConnection, QueryResult, QueryDsl, ExpressionMethods, SelectableHelper};
+
+use super::DieselRepository;
+
+/// Where the routes read and write exchange rates
+#[rocket::async_trait]
+pub trait CurrencyRepository: Send + Sync {
+    /// Every exchange rate, ordered by currency
+    async fn list_rates(&self) -> QueryResult<Vec<ExchangeRate>>;
+
+    async fn get_rate(&self, currency: &str) -> QueryResult<ExchangeRate>;
+
+    /// Sets the currency's rate, adding the currency if it's new
+    async fn upsert_rate(&self, currency: &str, rate_micros: i64) -> QueryResult<ExchangeRate>;
+}
+
+#[rocket::async_trait]
+impl CurrencyRepository for DieselRepository {
+    async fn list_rates(&self) -> QueryResult<Vec<ExchangeRate>> {
+        list_rates(&mut *self.connection().await?).await
+    }
+
+    async fn get_rate(&self, currency: &str) -> QueryResult<ExchangeRate> {
+        get_rate(currency, &mut *self.connection().await?).await
+    }
+
+    async fn upsert_rate(&self, currency: &str, rate_micros: i64) -> QueryResult<ExchangeRate> {
+        upsert_rate(currency, rate_micros, &mut *self.connection().await?).await
+    }
+}
+
+pub async fn list_rates(db: &mut AsyncPgConnection) -> QueryResult<Vec<ExchangeRate>> {
+    Ok(
+        exchange_rates::table
+            .order(exchange_rates::currency)
+            .select(ExchangeRate::as_select())
+            .load(db)
+            .await?
+    )
+}
+
+async fn get_rate(currency: &str, db: &mut AsyncPgConnection) -> QueryResult<ExchangeRate> {
+    Ok(
+        exchange_rates::table
+            .find(currency)
+            .select(ExchangeRate::as_select())
+            .get_result(db)
+            .await?
+    )
+}
+
+async fn upsert_rate(currency: &str, rate_micros: i64, db: &mut AsyncPgConnection) -> QueryResult<ExchangeRate> {
+    Ok(
+        diesel::insert_into(exchange_rates::table)
+            .values((exchange_rates::currency.eq(currency), exchange_rates::rate_micros.eq(rate_micros)))
+            .on_conflict(exchange_rates::currency)
+            .do_update()
+            .set(exchange_rates::rate_micros.eq(excluded(exchange_rates::rate_micros)))
+            .returning(ExchangeRate::as_returning())
+            .get_result(db)
+            .await?
+    )
+}
@@ -0,0 +1,930 @@
+//! Repositories over data kept in memory, for exercising the routes without
+//! a database
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+use api_types::{
+    component::{Component, ComponentPrice, ComponentQuery, ComponentSort},
+    currency::ExchangeRate,
+    money::Money,
+    page::Page,
+    revision::{RevisionComponent, RevisionItem, RigRevisionWithComponents},
+    rig::{Rig, RigComponentData, RigComponentItem, RigValuation, RigWithComponents, ValuationItem},
+    slot::Slot,
+    types::Role,
+    user::User,
+};
+use rocket::{
+    futures::{stream::{self, BoxStream}, StreamExt},
+    response::Debug,
+};
+use rocket_db_pools::diesel::{
+    result::{DatabaseErrorKind, Error},
+    QueryResult,
+};
+use time::{Date, OffsetDateTime, PrimitiveDateTime};
+use uuid::Uuid;
+
+use crate::{
+    auth::{generate_token, hash_token, SESSION_TTL},
+    dto::component::ComponentChangeset,
+    error::ApiResult,
+    models::{revision::RigRevision, rig::{generate_share_slug, RigComponent}, user::Session},
+    money::{Convert, Converter},
+    validation::rig::Catalog,
+};
+
+use super::{
+    revision::RestoreCheck,
+    rig::{ComponentsCheck, Fork, RigComponentRecord},
+    ComponentRepository, CurrencyRepository, RevisionRepository, RigRepository, SessionRepository, SlotRepository, UserRepository,
+};
+
+fn not_found() -> Debug<Error> {
+    Debug(Error::NotFound)
+}
+
+/// Fails the way Postgres does when `constraint` is violated
+fn violation(kind: DatabaseErrorKind, constraint: &str) -> Debug<Error> {
+    let message = match kind {
+        DatabaseErrorKind::UniqueViolation => format!("duplicate key value violates unique constraint \"{constraint}\""),
+        _ => format!("insert or update violates foreign key constraint \"{constraint}\""),
+    };
+
+    Debug(Error::DatabaseError(kind, Box::new(message)))
+}
+
+#[derive(Default)]
+struct Store {
+    slots: Vec<Slot>,
+    rates: Vec<ExchangeRate>,
+    components: Vec<Component>,
+    prices: Vec<ComponentPrice>,
+    rigs: Vec<Rig>,
+    rig_components: Vec<RigComponent>,
+    /// Every rig's revisions, in the order they were recorded
+    revisions: Vec<(RigRevision, Vec<RevisionComponent>)>,
+    users: Vec<User>,
+    /// Sessions by the hash of their tokens
+    sessions: HashMap<String, Session>,
+}
+
+impl Store {
+    fn component(&self, id: Uuid) -> QueryResult<&Component> {
+        self.components.iter().find(|component| component.id == id).ok_or_else(not_found)
+    }
+
+    fn rig(&self, id: Uuid) -> QueryResult<&Rig> {
+        self.rigs.iter().find(|rig| rig.id == id).ok_or_else(not_found)
+    }
+
+    fn user(&self, id: Uuid) -> QueryResult<&User> {
+        self.users.iter().find(|user| user.id == id).ok_or_else(not_found)
+    }
+
+    fn create_user(&mut self, user: User) -> QueryResult<User> {
+        if self.users.iter().any(|other| other.id == user.id) {
+            return Err(violation(DatabaseErrorKind::UniqueViolation, "users_pkey"));
+        }
+        if self.users.iter().any(|other| other.email == user.email) {
+            return Err(violation(DatabaseErrorKind::UniqueViolation, "users_email_key"));
+        }
+
+        self.users.push(user.clone());
+
+        Ok(user)
+    }
+
+    /// Every slot, in the order they're listed
+    fn slots(&self) -> Vec<Slot> {
+        let mut slots = self.slots.clone();
+        slots.sort_by(|a, b| (a.position, &a.key).cmp(&(b.position, &b.key)));
+
+        slots
+    }
+
+    fn rate(&self, currency: &str) -> i64 {
+        self.rates
+            .iter()
+            .find(|rate| rate.currency == currency)
+            .map_or(0, |rate| rate.rate_micros)
+    }
+
+    /// The component's price in millionths of the base currency's cents
+    fn base_price(&self, component: &Component) -> i128 {
+        i128::from(component.price.amount) * i128::from(self.rate(&component.price.currency))
+    }
+
+    /// The rig's components, in the order they were added
+    fn parts(&self, rig_id: Uuid) -> Vec<(RigComponent, Component)> {
+        self.rig_components
+            .iter()
+            .filter(|rig_component| rig_component.rig_id == rig_id)
+            .filter_map(|rig_component| {
+                let component = self.component(rig_component.component_id).ok()?;
+                Some((rig_component.clone(), component.clone()))
+            })
+            .collect()
+    }
+
+    fn priced(&self, rig: &Rig, converter: &Converter) -> ApiResult<RigWithComponents> {
+        let items = self.parts(rig.id)
+            .into_iter()
+            .map(|(rig_component, component)| Ok(RigComponentItem::new(component.converted(converter)?, rig_component.quantity)))
+            .collect::<ApiResult<_>>()?;
+
+        Ok(rig.clone().with_components(items, converter.currency()))
+    }
+
+    /// Checks the constraints of the `components` table, for `component`
+    /// going in as a new row or in place of the one with its id
+    fn check_component(&self, component: &Component) -> QueryResult<()> {
+        if !self.slots.iter().any(|slot| slot.key == component.slot) {
+            return Err(violation(DatabaseErrorKind::ForeignKeyViolation, "components_slot_fkey"));
+        }
+        if !self.rates.iter().any(|rate| rate.currency == component.price.currency) {
+            return Err(violation(DatabaseErrorKind::ForeignKeyViolation, "components_currency_fkey"));
+        }
+
+        let taken = self.components.iter().any(|other| {
+            other.id != component.id && other.manufacturer == component.manufacturer && other.model == component.model
+        });
+        if taken {
+            return Err(violation(DatabaseErrorKind::UniqueViolation, "components_manufacturer_model_key"));
+        }
+
+        Ok(())
+    }
+
+    /// Keeps the component's price history, like the table's trigger does
+    fn record_price(&mut self, component: &Component) {
+        let id = self.prices.last().map_or(1, |price| price.id + 1);
+        self.prices.push(ComponentPrice {
+            id,
+            component_id: component.id,
+            price: component.price.clone(),
+            changed_at: OffsetDateTime::now_utc(),
+        });
+    }
+
+    fn create_component(&mut self, component: Component) -> QueryResult<Component> {
+        if self.component(component.id).is_ok() {
+            return Err(violation(DatabaseErrorKind::UniqueViolation, "components_pkey"));
+        }
+        self.check_component(&component)?;
+
+        self.record_price(&component);
+        self.components.push(component.clone());
+
+        Ok(component)
+    }
+
+    fn update_component(&mut self, component: Component) -> QueryResult<Component> {
+        let previous = self.component(component.id)?.price.clone();
+        self.check_component(&component)?;
+
+        if previous != component.price {
+            self.record_price(&component);
+        }
+        if let Some(stored) = self.components.iter_mut().find(|stored| stored.id == component.id) {
+            *stored = component.clone();
+        }
+
+        Ok(component)
+    }
+
+    fn create_rig(&mut self, rig: Rig, rig_components: Vec<RigComponent>) -> QueryResult<()> {
+        if self.rig(rig.id).is_ok() {
+            return Err(violation(DatabaseErrorKind::UniqueViolation, "rigs_pkey"));
+        }
+        self.check_rig_components(&rig_components)?;
+
+        let id = rig.id;
+        self.rigs.push(rig);
+        self.rig_components.extend(rig_components);
+        self.record_revision(id)
+    }
+
+    fn rename_rig(&mut self, id: Uuid, name: &str) -> QueryResult<Rig> {
+        let rig = self.rigs.iter_mut().find(|rig| rig.id == id).ok_or_else(not_found)?;
+        rig.name = name.to_owned();
+        let rig = rig.clone();
+
+        self.record_revision(id)?;
+
+        Ok(rig)
+    }
+
+    fn replace_rig_components(&mut self, id: Uuid, rig_components: Vec<RigComponent>) -> QueryResult<()> {
+        self.rig(id)?;
+        self.check_rig_components(&rig_components)?;
+
+        self.rig_components.retain(|rig_component| rig_component.rig_id != id);
+        self.rig_components.extend(rig_components);
+        self.record_revision(id)
+    }
+
+    /// Snapshots the rig as it is now into its next revision
+    fn record_revision(&mut self, rig_id: Uuid) -> QueryResult<()> {
+        let rig = self.rig(rig_id)?;
+        let last = self.revisions
+            .iter()
+            .filter(|(revision, _)| revision.rig_id == rig_id)
+            .map(|(revision, _)| revision.number)
+            .max();
+        let revision = RigRevision::new(rig_id, last.unwrap_or_default() + 1, rig.name.clone());
+
+        let components = self.parts(rig_id)
+            .into_iter()
+            .map(|(rig_component, component)| RevisionComponent {
+                component_id: component.id,
+                manufacturer: component.manufacturer,
+                model: component.model,
+                slot: component.slot,
+                quantity: rig_component.quantity,
+                price: component.price,
+            })
+            .collect();
+        self.revisions.push((revision, components));
+
+        Ok(())
+    }
+
+    fn revision(&self, rig_id: Uuid, number: i32) -> QueryResult<&(RigRevision, Vec<RevisionComponent>)> {
+        self.revisions
+            .iter()
+            .find(|(revision, _)| revision.rig_id == rig_id && revision.number == number)
+            .ok_or_else(not_found)
+    }
+
+    /// The revision's components ordered like the database orders them
+    fn priced_revision(revision: &RigRevision, components: &[RevisionComponent], converter: &Converter) -> ApiResult<RigRevisionWithComponents> {
+        let mut components = components.to_vec();
+        components.sort_by(|a, b| (&a.slot, a.component_id).cmp(&(&b.slot, b.component_id)));
+
+        let items = components
+            .into_iter()
+            .map(|component| Ok(RevisionItem::new(component.converted(converter)?)))
+            .collect::<ApiResult<_>>()?;
+
+        Ok(revision.clone().with_components(items, converter.currency()))
+    }
+
+    /// The components among `ids`, and every slot
+    fn catalog(&self, ids: &[Uuid]) -> Catalog {
+        Catalog {
+            components: self.components
+                .iter()
+                .filter(|component| ids.contains(&component.id))
+                .cloned()
+                .collect(),
+            slots: self.slots(),
+        }
+    }
+
+    /// Checks the constraints of the `rig_components` table for
+    /// `rig_components` going in next to the rows of other rigs
+    fn check_rig_components(&self, rig_components: &[RigComponent]) -> QueryResult<()> {
+        for (index, rig_component) in rig_components.iter().enumerate() {
+            if self.component(rig_component.component_id).is_err() {
+                return Err(violation(DatabaseErrorKind::ForeignKeyViolation, "rig_components_component_id_fkey"));
+            }
+
+            let repeated = rig_components[..index]
+                .iter()
+                .any(|other| other.rig_id == rig_component.rig_id && other.component_id == rig_component.component_id);
+            if repeated {
+                return Err(violation(DatabaseErrorKind::UniqueViolation, "rig_components_pkey"));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Every repository, over data kept in memory and shared by every clone.
+/// It starts out like a freshly migrated database, with the default slots
+/// and exchange rates.
+#[derive(Clone)]
+pub struct InMemoryRepository {
+    store: Arc<RwLock<Store>>,
+}
+
+impl Default for InMemoryRepository {
+    fn default() -> Self {
+        let slot = |key: &str, name: &str, max_per_rig: Option<i32>, position: i32| Slot {
+            key: key.to_owned(),
+            name: name.to_owned(),
+            icon: key.to_owned(),
+            min_per_rig: 1,
+            max_per_rig,
+            position,
+        };
+        let rate = |currency: &str, rate_micros: i64| ExchangeRate {
+            currency: currency.to_owned(),
+            rate_micros,
+            updated_at: OffsetDateTime::now_utc(),
+        };
+
+        let store = Store {
+            slots: vec![
+                slot("cpu", "CPU", Some(1), 0),
+                slot("gpu", "GPU", Some(1), 1),
+                slot("memory", "Memory", Some(4), 2),
+                slot("storage", "Storage", None, 3),
+            ],
+            rates: vec![rate("BRL", 1_000_000), rate("EUR", 5_900_000), rate("USD", 5_400_000)],
+            ..Store::default()
+        };
+
+        Self { store: Arc::new(RwLock::new(store)) }
+    }
+}
+
+impl InMemoryRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // A panic while holding the lock can't leave the store half written, as
+    // every write checks its constraints before changing anything
+    fn read(&self) -> RwLockReadGuard<'_, Store> {
+        self.store.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, Store> {
+        self.store.write().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Adds `user` and starts a session for it, returning its bearer token
+    pub fn log_in(&self, user: User) -> String {
+        let token = generate_token();
+        let now = OffsetDateTime::now_utc();
+        let session = Session {
+            token_hash: hash_token(&token),
+            user_id: user.id,
+            created_at: now,
+            expires_at: now + SESSION_TTL,
+        };
+
+        let mut store = self.write();
+        store.create_user(user).expect("the user is new");
+        store.sessions.insert(session.token_hash.clone(), session);
+
+        token
+    }
+}
+
+#[rocket::async_trait]
+impl ComponentRepository for InMemoryRepository {
+    async fn get_component(&self, id: Uuid) -> QueryResult<Component> {
+        self.read().component(id).cloned()
+    }
+
+    async fn stream_components(&self) -> QueryResult<BoxStream<'static, Result<Component, Error>>> {
+        let mut components = self.read().components.clone();
+        components.sort_by(|a, b| (&a.manufacturer, &a.model, a.id).cmp(&(&b.manufacturer, &b.model, b.id)));
+
+        Ok(stream::iter(components.into_iter().map(Ok)).boxed())
+    }
+
+    async fn list_components(&self, query: &ComponentQuery, rate: i64) -> QueryResult<Page<Component>> {
+        let store = self.read();
+        // Price bounds in millionths of the base currency's cents, like the prices
+        let bound = |price: i64| i128::from(price) * i128::from(rate);
+
+        let mut matching: Vec<&Component> = store.components
+            .iter()
+            .filter(|component| query.slot.as_ref().is_none_or(|slot| &component.slot == slot))
+            .filter(|component| query.manufacturer.as_ref().is_none_or(|manufacturer| component.manufacturer.to_lowercase() == manufacturer.to_lowercase()))
+            .filter(|component| query.min_price.is_none_or(|min_price| store.base_price(component) >= bound(min_price)))
+            .filter(|component| query.max_price.is_none_or(|max_price| store.base_price(component) <= bound(max_price)))
+            .collect();
+
+        matching.sort_by(|a, b| {
+            let order = match query.sort {
+                None => (&a.manufacturer, &a.model).cmp(&(&b.manufacturer, &b.model)),
+                Some(ComponentSort::PriceAsc) => store.base_price(a).cmp(&store.base_price(b)),
+                Some(ComponentSort::PriceDesc) => store.base_price(b).cmp(&store.base_price(a)),
+                Some(ComponentSort::Model) => a.model.cmp(&b.model),
+            };
+            order.then(a.id.cmp(&b.id))
+        });
+
+        let pagination = query.pagination();
+        let total = matching.len() as i64;
+        let items = matching
+            .into_iter()
+            .skip(pagination.offset() as usize)
+            .take(pagination.per_page as usize)
+            .cloned()
+            .collect();
+
+        Ok(Page::new(items, pagination, total))
+    }
+
+    /// Ranks exact model hits first, then by model, without the weighing of
+    /// the full text search
+    async fn search_components(&self, terms: &str, limit: i64) -> QueryResult<Vec<Component>> {
+        let words = |text: &str| -> Vec<String> {
+            text.split(|c: char| !c.is_alphanumeric())
+                .filter(|word| !word.is_empty())
+                .map(str::to_lowercase)
+                .collect()
+        };
+        let wanted = words(terms);
+        if wanted.is_empty() {
+            return Ok(Vec::new());
+        }
+        let exact = terms.trim().to_lowercase();
+
+        let mut found: Vec<Component> = self.read().components
+            .iter()
+            .filter(|component| {
+                let known = words(&format!("{} {}", component.model, component.manufacturer));
+                wanted.iter().all(|word| known.iter().any(|known| known.starts_with(word.as_str())))
+            })
+            .cloned()
+            .collect();
+
+        found.sort_by(|a, b| {
+            (b.model.to_lowercase() == exact)
+                .cmp(&(a.model.to_lowercase() == exact))
+                .then_with(|| a.model.cmp(&b.model))
+        });
+        found.truncate(limit.max(0) as usize);
+
+        Ok(found)
+    }
+
+    async fn list_components_by_id(&self, ids: &[Uuid]) -> QueryResult<Vec<Component>> {
+        Ok(self.read().components
+            .iter()
+            .filter(|component| ids.contains(&component.id))
+            .cloned()
+            .collect())
+    }
+
+    async fn find_component_ids(&self, names: &[(String, String)]) -> QueryResult<HashMap<(String, String), Uuid>> {
+        Ok(self.read().components
+            .iter()
+            .filter(|component| names.iter().any(|(manufacturer, model)| &component.manufacturer == manufacturer && &component.model == model))
+            .map(|component| ((component.manufacturer.clone(), component.model.clone()), component.id))
+            .collect())
+    }
+
+    async fn create_component(&self, component: Component) -> QueryResult<Component> {
+        self.write().create_component(component)
+    }
+
+    async fn update_component(&self, component: Component) -> QueryResult<Component> {
+        self.write().update_component(component)
+    }
+
+    async fn patch_component(&self, id: Uuid, changes: ComponentChangeset) -> QueryResult<Component> {
+        let mut store = self.write();
+        let mut component = store.component(id)?.clone();
+
+        let ComponentChangeset { manufacturer, model, slot, price_cents, currency, socket, memory_generations, interfaces } = changes;
+        if let Some(manufacturer) = manufacturer {
+            component.manufacturer = manufacturer;
+        }
+        if let Some(model) = model {
+            component.model = model;
+        }
+        if let Some(slot) = slot {
+            component.slot = slot;
+        }
+        if let Some(price_cents) = price_cents {
+            component.price.amount = price_cents;
+        }
+        if let Some(currency) = currency {
+            component.price.currency = currency;
+        }
+        if let Some(socket) = socket {
+            component.socket = Some(socket);
+        }
+        if let Some(memory_generations) = memory_generations {
+            component.memory_generations = memory_generations;
+        }
+        if let Some(interfaces) = interfaces {
+            component.interfaces = interfaces;
+        }
+
+        store.update_component(component)
+    }
+
+    async fn import_components(&self, components: Vec<Component>) -> QueryResult<Vec<Component>> {
+        let mut store = self.write();
+        let (stored, prices) = (store.components.clone(), store.prices.clone());
+
+        let written = components
+            .into_iter()
+            .map(|component| {
+                let existing = store.components
+                    .iter()
+                    .find(|other| other.manufacturer == component.manufacturer && other.model == component.model)
+                    .map(|other| other.id);
+                match existing {
+                    Some(id) => store.update_component(Component { id, ..component }),
+                    None => store.create_component(component),
+                }
+            })
+            .collect::<QueryResult<Vec<_>>>();
+
+        // Rolled back like the transaction would be
+        if written.is_err() {
+            store.components = stored;
+            store.prices = prices;
+        }
+
+        written
+    }
+
+    async fn delete_component(&self, id: Uuid) -> QueryResult<Vec<Rig>> {
+        let mut store = self.write();
+        store.component(id)?;
+
+        let rigs: Vec<Rig> = store.rigs
+            .iter()
+            .filter(|rig| store.rig_components.iter().any(|rig_component| rig_component.rig_id == rig.id && rig_component.component_id == id))
+            .cloned()
+            .collect();
+        if rigs.is_empty() {
+            store.components.retain(|component| component.id != id);
+            store.prices.retain(|price| price.component_id != id);
+        }
+
+        Ok(rigs)
+    }
+
+    async fn list_component_prices(&self, id: Uuid) -> QueryResult<Vec<ComponentPrice>> {
+        let store = self.read();
+        store.component(id)?;
+
+        // Kept in the order they were recorded, which is also by time
+        Ok(store.prices
+            .iter()
+            .filter(|price| price.component_id == id)
+            .cloned()
+            .collect())
+    }
+
+    async fn list_slots(&self) -> QueryResult<Vec<Slot>> {
+        Ok(self.read().slots())
+    }
+
+    async fn list_rates(&self) -> QueryResult<Vec<ExchangeRate>> {
+        let mut rates = self.read().rates.clone();
+        rates.sort_by(|a, b| a.currency.cmp(&b.currency));
+
+        Ok(rates)
+    }
+}
+
+#[rocket::async_trait]
+impl RigRepository for InMemoryRepository {
+    async fn list_rigs(&self, owner_id: Option<Uuid>, converter: &Converter) -> ApiResult<Vec<RigWithComponents>> {
+        let store = self.read();
+
+        store.rigs
+            .iter()
+            .filter(|rig| owner_id.is_none() || rig.owner_id == owner_id)
+            .map(|rig| store.priced(rig, converter))
+            .collect()
+    }
+
+    async fn stream_rig_components(&self, owner_id: Option<Uuid>) -> QueryResult<BoxStream<'static, Result<RigComponentRecord, Error>>> {
+        let store = self.read();
+
+        let mut rigs: Vec<&Rig> = store.rigs
+            .iter()
+            .filter(|rig| owner_id.is_none() || rig.owner_id == owner_id)
+            .collect();
+        rigs.sort_by(|a, b| (&a.name, a.id).cmp(&(&b.name, b.id)));
+
+        let mut rows: Vec<RigComponentRecord> = Vec::new();
+        for rig in rigs {
+            let mut parts = store.parts(rig.id);
+            parts.sort_by(|(_, a), (_, b)| (&a.slot, &a.model).cmp(&(&b.slot, &b.model)));
+
+            match parts.is_empty() {
+                true => rows.push((rig.clone(), None)),
+                false => rows.extend(parts.into_iter().map(|part| (rig.clone(), Some(part)))),
+            }
+        }
+
+        Ok(stream::iter(rows.into_iter().map(Ok)).boxed())
+    }
+
+    async fn get_rig(&self, id: Uuid, converter: &Converter) -> ApiResult<RigWithComponents> {
+        let store = self.read();
+
+        store.priced(store.rig(id)?, converter)
+    }
+
+    async fn get_rig_valuation(&self, id: Uuid, at: Date, converter: &Converter) -> ApiResult<RigValuation> {
+        let until = at.next_day().map_or(PrimitiveDateTime::MAX, |day| day.midnight()).assume_utc();
+        let store = self.read();
+        let rig = store.rig(id)?.clone();
+
+        let items = store.parts(id)
+            .into_iter()
+            .map(|(rig_component, component)| {
+                let price: Option<&Money> = store.prices
+                    .iter()
+                    .filter(|price| price.component_id == component.id && price.changed_at < until)
+                    .max_by_key(|price| (price.changed_at, price.id))
+                    .map(|price| &price.price);
+                let price = price.map(|price| converter.convert(price)).transpose()?;
+
+                Ok(ValuationItem::new(component.converted(converter)?, rig_component.quantity, price))
+            })
+            .collect::<ApiResult<_>>()?;
+
+        Ok(RigValuation::new(rig, at, items, converter.currency()))
+    }
+
+    async fn get_rig_parts(&self, id: Uuid) -> QueryResult<(Rig, Vec<(RigComponent, Component)>)> {
+        let store = self.read();
+
+        Ok((store.rig(id)?.clone(), store.parts(id)))
+    }
+
+    async fn get_shared_rig(&self, slug: &str, converter: &Converter) -> ApiResult<RigWithComponents> {
+        let store = self.read();
+        let rig = store.rigs
+            .iter()
+            .find(|rig| rig.share_slug.as_deref() == Some(slug))
+            .ok_or_else(not_found)?;
+
+        store.priced(rig, converter)
+    }
+
+    async fn get_rig_owner(&self, id: Uuid) -> QueryResult<Option<Uuid>> {
+        Ok(self.read().rig(id)?.owner_id)
+    }
+
+    async fn create_rig(&self, rig: Rig, rig_components: Vec<RigComponent>, check: ComponentsCheck) -> ApiResult<()> {
+        let mut store = self.write();
+        let ids: Vec<Uuid> = rig_components.iter().map(|rig_component| rig_component.component_id).collect();
+        check(&store.catalog(&ids))?;
+
+        Ok(store.create_rig(rig, rig_components)?)
+    }
+
+    async fn clone_rig(&self, id: Uuid, component_ids: &[Uuid], fork: Fork) -> ApiResult<Rig> {
+        let mut store = self.write();
+        let original = store.rig(id)?.clone();
+        let original_components = store.parts(id);
+
+        let ids: Vec<Uuid> = original_components
+            .iter()
+            .map(|(rig_component, _)| rig_component.component_id)
+            .chain(component_ids.iter().copied())
+            .collect();
+        let (clone, rig_components) = fork(original, original_components, &store.catalog(&ids))?;
+        store.create_rig(clone.clone(), rig_components)?;
+
+        Ok(clone)
+    }
+
+    async fn rename_rig(&self, id: Uuid, name: &str) -> QueryResult<Rig> {
+        self.write().rename_rig(id, name)
+    }
+
+    async fn replace_rig_components(&self, id: Uuid, rig_components: Vec<RigComponent>, check: ComponentsCheck) -> ApiResult<()> {
+        let mut store = self.write();
+        store.rig(id)?;
+        let ids: Vec<Uuid> = rig_components.iter().map(|rig_component| rig_component.component_id).collect();
+        check(&store.catalog(&ids))?;
+
+        Ok(store.replace_rig_components(id, rig_components)?)
+    }
+
+    async fn share_rig(&self, id: Uuid, shared: bool) -> QueryResult<Rig> {
+        let mut store = self.write();
+        let rig = store.rigs.iter_mut().find(|rig| rig.id == id).ok_or_else(not_found)?;
+        rig.share_slug = match shared {
+            true => Some(rig.share_slug.take().unwrap_or_else(generate_share_slug)),
+            false => None,
+        };
+
+        Ok(rig.clone())
+    }
+
+    async fn delete_rig(&self, id: Uuid) -> QueryResult<usize> {
+        let mut store = self.write();
+
+        let before = store.rigs.len();
+        store.rigs.retain(|rig| rig.id != id);
+        store.rig_components.retain(|rig_component| rig_component.rig_id != id);
+        store.revisions.retain(|(revision, _)| revision.rig_id != id);
+        for rig in store.rigs.iter_mut().filter(|rig| rig.parent_rig_id == Some(id)) {
+            rig.parent_rig_id = None;
+        }
+
+        Ok(before - store.rigs.len())
+    }
+}
+
+#[rocket::async_trait]
+impl RevisionRepository for InMemoryRepository {
+    async fn list_revisions(&self, rig_id: Uuid, converter: &Converter) -> ApiResult<Vec<RigRevisionWithComponents>> {
+        let store = self.read();
+        store.rig(rig_id)?;
+
+        // Kept in the order they were recorded, which is also by number
+        store.revisions
+            .iter()
+            .filter(|(revision, _)| revision.rig_id == rig_id)
+            .map(|(revision, components)| Store::priced_revision(revision, components, converter))
+            .collect()
+    }
+
+    async fn get_revision(&self, rig_id: Uuid, number: i32, converter: &Converter) -> ApiResult<RigRevisionWithComponents> {
+        let store = self.read();
+        let (revision, components) = store.revision(rig_id, number)?;
+
+        Store::priced_revision(revision, components, converter)
+    }
+
+    async fn restore_revision(&self, rig_id: Uuid, number: i32, check: RestoreCheck) -> ApiResult<()> {
+        let mut store = self.write();
+        let (revision, components) = store.revision(rig_id, number)?;
+        let name = revision.name.clone();
+
+        let mut components = components.clone();
+        components.sort_by(|a, b| (&a.slot, a.component_id).cmp(&(&b.slot, b.component_id)));
+        let restored: Vec<RigComponentData> = components
+            .into_iter()
+            .map(|component| RigComponentData { component_id: component.component_id, quantity: component.quantity })
+            .collect();
+
+        let ids: Vec<Uuid> = restored.iter().map(|restored| restored.component_id).collect();
+        check(&restored, &store.catalog(&ids))?;
+
+        // Swapped in without a revision of their own: the rename records one
+        // with both changes, like the transaction does
+        let rig_components = RigComponent::for_rig(rig_id, restored);
+        store.check_rig_components(&rig_components)?;
+        store.rig_components.retain(|rig_component| rig_component.rig_id != rig_id);
+        store.rig_components.extend(rig_components);
+        store.rename_rig(rig_id, &name)?;
+
+        Ok(())
+    }
+}
+
+#[rocket::async_trait]
+impl SessionRepository for InMemoryRepository {
+    async fn get_session_user(&self, token_hash: &str) -> QueryResult<User> {
+        let store = self.read();
+        let session = store.sessions
+            .get(token_hash)
+            .filter(|session| session.expires_at > OffsetDateTime::now_utc())
+            .ok_or_else(not_found)?;
+
+        store.user(session.user_id).cloned()
+    }
+}
+
+#[rocket::async_trait]
+impl UserRepository for InMemoryRepository {
+    async fn create_user(&self, user: User) -> QueryResult<User> {
+        self.write().create_user(user)
+    }
+
+    async fn list_users(&self) -> QueryResult<Vec<User>> {
+        let mut users = self.read().users.clone();
+        users.sort_by(|a, b| a.email.cmp(&b.email));
+
+        Ok(users)
+    }
+
+    async fn get_user(&self, id: Uuid) -> QueryResult<User> {
+        self.read().user(id).cloned()
+    }
+
+    async fn find_user_by_email(&self, email: &str) -> QueryResult<User> {
+        self.read().users.iter().find(|user| user.email == email).cloned().ok_or_else(not_found)
+    }
+
+    async fn set_role(&self, id: Uuid, role: Role) -> QueryResult<Option<User>> {
+        let mut store = self.write();
+        let admins: Vec<Uuid> = store.users.iter().filter(|user| user.role == Role::Admin).map(|user| user.id).collect();
+        if role != Role::Admin && admins == [id] {
+            return Ok(None);
+        }
+
+        let user = store.users.iter_mut().find(|user| user.id == id).ok_or_else(not_found)?;
+        user.role = role;
+
+        Ok(Some(user.clone()))
+    }
+
+    async fn create_session(&self, session: Session) -> QueryResult<Session> {
+        let mut store = self.write();
+        store.user(session.user_id).map_err(|_| violation(DatabaseErrorKind::ForeignKeyViolation, "sessions_user_id_fkey"))?;
+        if store.sessions.contains_key(&session.token_hash) {
+            return Err(violation(DatabaseErrorKind::UniqueViolation, "sessions_pkey"));
+        }
+        store.sessions.insert(session.token_hash.clone(), session.clone());
+
+        Ok(session)
+    }
+
+    async fn delete_session(&self, token_hash: &str) -> QueryResult<usize> {
+        Ok(self.write().sessions.remove(token_hash).map_or(0, |_| 1))
+    }
+
+    async fn delete_expired_sessions(&self) -> QueryResult<usize> {
+        let now = OffsetDateTime::now_utc();
+        let mut store = self.write();
+
+        let before = store.sessions.len();
+        store.sessions.retain(|_, session| session.expires_at > now);
+
+        Ok(before - store.sessions.len())
+    }
+}
+
+#[rocket::async_trait]
+impl SlotRepository for InMemoryRepository {
+    async fn list_slots(&self) -> QueryResult<Vec<Slot>> {
+        Ok(self.read().slots())
+    }
+
+    async fn get_slot(&self, key: &str) -> QueryResult<Slot> {
+        self.read().slots.iter().find(|slot| slot.key == key).cloned().ok_or_else(not_found)
+    }
+
+    async fn create_slot(&self, slot: Slot) -> QueryResult<Slot> {
+        let mut store = self.write();
+        if store.slots.iter().any(|other| other.key == slot.key) {
+            return Err(violation(DatabaseErrorKind::UniqueViolation, "slots_pkey"));
+        }
+        store.slots.push(slot.clone());
+
+        Ok(slot)
+    }
+
+    async fn update_slot(&self, slot: Slot) -> QueryResult<Slot> {
+        let mut store = self.write();
+        let stored = store.slots.iter_mut().find(|other| other.key == slot.key).ok_or_else(not_found)?;
+        *stored = slot.clone();
+
+        Ok(slot)
+    }
+
+    async fn delete_slot(&self, key: &str) -> QueryResult<Vec<Component>> {
+        let mut store = self.write();
+        if !store.slots.iter().any(|slot| slot.key == key) {
+            return Err(not_found());
+        }
+
+        let components: Vec<Component> = store.components
+            .iter()
+            .filter(|component| component.slot == key)
+            .cloned()
+            .collect();
+        if components.is_empty() {
+            store.slots.retain(|slot| slot.key != key);
+        }
+
+        Ok(components)
+    }
+}
+
+#[rocket::async_trait]
+impl CurrencyRepository for InMemoryRepository {
+    async fn list_rates(&self) -> QueryResult<Vec<ExchangeRate>> {
+        let mut rates = self.read().rates.clone();
+        rates.sort_by(|a, b| a.currency.cmp(&b.currency));
+
+        Ok(rates)
+    }
+
+    async fn get_rate(&self, currency: &str) -> QueryResult<ExchangeRate> {
+        self.read().rates.iter().find(|rate| rate.currency == currency).cloned().ok_or_else(not_found)
+    }
+
+    async fn upsert_rate(&self, currency: &str, rate_micros: i64) -> QueryResult<ExchangeRate> {
+        let mut store = self.write();
+        let rate = ExchangeRate {
+            currency: currency.to_owned(),
+            rate_micros,
+            updated_at: OffsetDateTime::now_utc(),
+        };
+
+        match store.rates.iter_mut().find(|stored| stored.currency == currency) {
+            Some(stored) => *stored = rate.clone(),
+            None => store.rates.push(rate.clone()),
+        }
+
+        Ok(rate)
+    }
+}
@@ -1,2 +1,90 @@
+use rocket::{fairing::AdHoc, response::Debug, Build, Rocket};
+use rocket_db_pools::{
+    diesel::{
+        pooled_connection::deadpool::Object,
+        result::{DatabaseErrorKind, Error},
+        AsyncPgConnection, PgPool, QueryResult,
+    },
+    Database,
+};
+
+use crate::database::Db;
+
+pub use self::{
+    component::ComponentRepository,
+    currency::CurrencyRepository,
+    revision::RevisionRepository,
+    rig::RigRepository,
+    slot::SlotRepository,
+    user::{SessionRepository, UserRepository},
+};
+
 pub mod component;
+pub mod currency;
+#[cfg(test)]
+pub mod memory;
+pub mod revision;
 pub mod rig;
+pub mod slot;
+pub mod user;
+
+/// The repositories, backed by the database's pool. Every call takes a
+/// connection of its own.
+#[derive(Clone)]
+pub struct DieselRepository {
+    pool: PgPool,
+}
+
+impl DieselRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Running out of connections fails like losing one would
+    async fn connection(&self) -> QueryResult<Object<AsyncPgConnection>> {
+        self.pool
+            .get()
+            .await
+            .map_err(|err| Debug(Error::DatabaseError(DatabaseErrorKind::ClosedConnection, Box::new(err.to_string()))))
+    }
+}
+
+/// Manages `repository` as every repository the routes are given, each as a
+/// `Box<dyn ...>` of its trait
+pub fn manage<R>(rocket: Rocket<Build>, repository: R) -> Rocket<Build>
+where
+    R: ComponentRepository
+        + RigRepository
+        + RevisionRepository
+        + SlotRepository
+        + CurrencyRepository
+        + UserRepository
+        + SessionRepository
+        + Clone
+        + 'static,
+{
+    rocket
+        .manage(Box::new(repository.clone()) as Box<dyn ComponentRepository>)
+        .manage(Box::new(repository.clone()) as Box<dyn RigRepository>)
+        .manage(Box::new(repository.clone()) as Box<dyn RevisionRepository>)
+        .manage(Box::new(repository.clone()) as Box<dyn SlotRepository>)
+        .manage(Box::new(repository.clone()) as Box<dyn CurrencyRepository>)
+        .manage(Box::new(repository.clone()) as Box<dyn UserRepository>)
+        .manage(Box::new(repository) as Box<dyn SessionRepository>)
+}
+
+/// Manages a [`DieselRepository`] over the pool of [`Db`], which must be
+/// attached first
+pub fn stage() -> AdHoc {
+    AdHoc::try_on_ignite("Repositories", |rocket| async {
+        let pool = match Db::fetch(&rocket) {
+            Some(db) => PgPool::clone(db),
+            None => {
+                rocket::error!("the database must be attached before the repositories");
+                return Err(rocket);
+            },
+        };
+
+        Ok(manage(rocket, DieselRepository::new(pool)))
+    })
+}
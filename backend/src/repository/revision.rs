@@ -0,0 +1,208 @@
+use std::collections::HashMap;
+
+use api_types::{
+    revision::{RevisionComponent, RevisionItem, RigRevisionWithComponents},
+    rig::{Rig, RigComponentData},
+    schema::{components, rig_components, rig_revision_components, rig_revisions, rigs},
+};
+use rocket_db_pools::diesel::{
+    self, prelude::RunQueryDsl, scoped_futures::ScopedFutureExt, AsyncConnection, AsyncPgConnection, BelongingToDsl, ExpressionMethods,
+    IntoSql, QueryDsl, QueryResult, SelectableHelper,
+};
+use uuid::Uuid;
+
+use crate::{
+    error::{ApiError, ApiResult},
+    models::{revision::RigRevision, rig::RigComponent},
+    money::{Convert, Converter},
+    validation::rig::Catalog,
+};
+
+use super::{component, rig, DieselRepository};
+
+/// Checks a revision's components against the catalog before they're
+/// restored, or fails with why they can't be
+pub type RestoreCheck = Box<dyn FnOnce(&[RigComponentData], &Catalog) -> ApiResult<()> + Send>;
+
+/// Where the routes read rig revisions and restore them. Revisions are
+/// recorded by the [`RigRepository`](super::RigRepository) writes.
+#[rocket::async_trait]
+pub trait RevisionRepository: Send + Sync {
+    /// Every revision of the rig, oldest first
+    async fn list_revisions(&self, rig_id: Uuid, converter: &Converter) -> ApiResult<Vec<RigRevisionWithComponents>>;
+
+    async fn get_revision(&self, rig_id: Uuid, number: i32, converter: &Converter) -> ApiResult<RigRevisionWithComponents>;
+
+    /// Brings the rig back to the name and components of revision `number`
+    /// and records that as its next revision, all in one transaction.
+    /// `check` gets the revision's components and the catalog with them.
+    async fn restore_revision(&self, rig_id: Uuid, number: i32, check: RestoreCheck) -> ApiResult<()>;
+}
+
+#[rocket::async_trait]
+impl RevisionRepository for DieselRepository {
+    async fn list_revisions(&self, rig_id: Uuid, converter: &Converter) -> ApiResult<Vec<RigRevisionWithComponents>> {
+        list_revisions(rig_id, converter, &mut *self.connection().await?).await
+    }
+
+    async fn get_revision(&self, rig_id: Uuid, number: i32, converter: &Converter) -> ApiResult<RigRevisionWithComponents> {
+        get_revision(rig_id, number, converter, &mut *self.connection().await?).await
+    }
+
+    async fn restore_revision(&self, rig_id: Uuid, number: i32, check: RestoreCheck) -> ApiResult<()> {
+        self.connection().await?.transaction(|db| async move {
+            rig::lock_rig(rig_id, db).await?;
+            let (name, restored) = get_revision_parts(rig_id, number, db).await?;
+
+            // Components may have left the catalog, or slots changed their
+            // rules, since the revision was taken
+            let ids: Vec<Uuid> = restored.iter().map(|component| component.component_id).collect();
+            check(&restored, &component::load_catalog(&ids, db).await?)?;
+
+            rig::rename_rig(rig_id, &name, db).await?;
+            rig::delete_rig_components(rig_id, db).await?;
+            rig::create_rig_components(&RigComponent::for_rig(rig_id, restored), db).await?;
+            record_revision(rig_id, db).await?;
+            Ok::<_, ApiError>(())
+        }.scope_boxed()).await
+    }
+}
+
+/// Snapshots the rig as it is now into its next revision. Meant to run in
+/// the transaction that changed the rig: the rig's row stays locked until it
+/// ends, so concurrent edits number their revisions one after the other.
+pub async fn record_revision(rig_id: Uuid, db: &mut AsyncPgConnection) -> QueryResult<RigRevision> {
+    let rig = rig::lock_rig(rig_id, db).await?;
+
+    let last: Option<i32> = RigRevision::belonging_to(&rig)
+        .select(diesel::dsl::max(rig_revisions::number))
+        .get_result(db)
+        .await?;
+    let revision = RigRevision::new(rig.id, last.unwrap_or_default() + 1, rig.name);
+
+    diesel::insert_into(rig_revisions::table)
+        .values(&revision)
+        .execute(db)
+        .await?;
+
+    let snapshot = rig_components::table
+        .inner_join(components::table)
+        .filter(rig_components::rig_id.eq(rig_id))
+        .select((
+            rig_components::rig_id,
+            revision.number.into_sql::<diesel::sql_types::Int4>(),
+            components::id,
+            components::manufacturer,
+            components::model,
+            components::slot,
+            rig_components::quantity,
+            components::price_cents,
+            components::currency,
+        ));
+
+    diesel::insert_into(rig_revision_components::table)
+        .values(snapshot)
+        .into_columns((
+            rig_revision_components::rig_id,
+            rig_revision_components::number,
+            rig_revision_components::component_id,
+            rig_revision_components::manufacturer,
+            rig_revision_components::model,
+            rig_revision_components::slot,
+            rig_revision_components::quantity,
+            rig_revision_components::price_cents,
+            rig_revision_components::currency,
+        ))
+        .execute(db)
+        .await?;
+
+    Ok(revision)
+}
+
+/// Loads the revisions' components, priced in the converter's currency
+async fn load_components(rig_id: Uuid, revisions: Vec<RigRevision>, converter: &Converter, db: &mut AsyncPgConnection) -> ApiResult<Vec<RigRevisionWithComponents>> {
+    let numbers: Vec<i32> = revisions.iter().map(|revision| revision.number).collect();
+
+    let mut components: HashMap<i32, Vec<RevisionItem>> = HashMap::new();
+    let rows = rig_revision_components::table
+        .filter(rig_revision_components::rig_id.eq(rig_id))
+        .filter(rig_revision_components::number.eq_any(&numbers))
+        .order_by((rig_revision_components::number, rig_revision_components::slot, rig_revision_components::component_id))
+        .select((
+            rig_revision_components::number,
+            (
+                rig_revision_components::component_id,
+                rig_revision_components::manufacturer,
+                rig_revision_components::model,
+                rig_revision_components::slot,
+                rig_revision_components::quantity,
+                (rig_revision_components::price_cents, rig_revision_components::currency),
+            ),
+        ))
+        .load::<(i32, RevisionComponent)>(db)
+        .await?;
+
+    for (number, component) in rows {
+        components
+            .entry(number)
+            .or_default()
+            .push(RevisionItem::new(component.converted(converter)?));
+    }
+
+    Ok(revisions
+        .into_iter()
+        .map(|revision| {
+            let items = components.remove(&revision.number).unwrap_or_default();
+            revision.with_components(items, converter.currency())
+        })
+        .collect())
+}
+
+async fn list_revisions(rig_id: Uuid, converter: &Converter, db: &mut AsyncPgConnection) -> ApiResult<Vec<RigRevisionWithComponents>> {
+    let rig = rigs::table
+        .find(rig_id)
+        .get_result::<Rig>(db)
+        .await?;
+
+    let revisions = RigRevision::belonging_to(&rig)
+        .order_by(rig_revisions::number)
+        .select(RigRevision::as_select())
+        .load(db)
+        .await?;
+
+    load_components(rig_id, revisions, converter, db).await
+}
+
+async fn get_revision(rig_id: Uuid, number: i32, converter: &Converter, db: &mut AsyncPgConnection) -> ApiResult<RigRevisionWithComponents> {
+    let revision = rig_revisions::table
+        .find((rig_id, number))
+        .select(RigRevision::as_select())
+        .get_result(db)
+        .await?;
+
+    let mut revisions = load_components(rig_id, vec![revision], converter, db).await?;
+
+    Ok(revisions.remove(0))
+}
+
+/// The revision's name and components as stored, unpriced
+async fn get_revision_parts(rig_id: Uuid, number: i32, db: &mut AsyncPgConnection) -> QueryResult<(String, Vec<RigComponentData>)> {
+    let name = rig_revisions::table
+        .find((rig_id, number))
+        .select(rig_revisions::name)
+        .get_result::<String>(db)
+        .await?;
+
+    let components = rig_revision_components::table
+        .filter(rig_revision_components::rig_id.eq(rig_id))
+        .filter(rig_revision_components::number.eq(number))
+        .order_by((rig_revision_components::slot, rig_revision_components::component_id))
+        .select((rig_revision_components::component_id, rig_revision_components::quantity))
+        .load::<(Uuid, i32)>(db)
+        .await?
+        .into_iter()
+        .map(|(component_id, quantity)| RigComponentData { component_id, quantity })
+        .collect();
+
+    Ok((name, components))
+}
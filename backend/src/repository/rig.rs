@@ -1,45 +1,405 @@
-use rocket_db_pools::{
-    diesel::{prelude::RunQueryDsl, QueryResult, QueryDsl, BelongingToDsl, ExpressionMethods, GroupedBy, Selectable, SelectableHelper},
-    Connection,
+use std::collections::HashMap;
+
+use api_types::{
+    component::Component,
+    money::Money,
+    rig::{Rig, RigComponentItem, RigValuation, RigWithComponents, ValuationItem},
+    schema::{component_prices, components, rigs, rig_components},
+};
+use rocket_db_pools::diesel::{
+    prelude::RunQueryDsl, result::Error, scoped_futures::ScopedFutureExt, AsyncConnection, AsyncPgConnection, QueryResult, QueryDsl,
+    BelongingToDsl, ExpressionMethods, GroupedBy, SelectableHelper,
 };
+use rocket::{
+    futures::{stream::BoxStream, Stream, StreamExt},
+    response::stream::stream,
+};
+use time::{Date, PrimitiveDateTime};
 use uuid::Uuid;
 
-use crate::{database::Db, dto::rig::RigWithComponents, models::{component::Component, rig::{Rig, RigComponent}}};
-use crate::schema::{components, rigs, rig_components};
+use crate::{
+    error::{ApiError, ApiResult},
+    models::rig::{generate_share_slug, RigComponent},
+    money::{Convert, Converter},
+    validation::rig::Catalog,
+};
+
+use super::{component, revision, DieselRepository};
+
+/// A rig's row in an export: the rig, and one of its components unless it
+/// has none
+pub type RigComponentRecord = (Rig, Option<(RigComponent, Component)>);
+
+/// Makes a clone out of the original rig and its components, checking it
+/// against the catalog, or fails with why it can't be made
+pub type Fork = Box<dyn FnOnce(Rig, Vec<(RigComponent, Component)>, &Catalog) -> ApiResult<(Rig, Vec<RigComponent>)> + Send>;
+
+/// Checks the components a rig is getting against the catalog with them, in
+/// the transaction that writes them, or fails with why they can't go in
+pub type ComponentsCheck = Box<dyn FnOnce(&Catalog) -> ApiResult<()> + Send>;
+
+/// Where the routes read and write rigs. Every write that changes a rig's
+/// name or components also records its next revision.
+#[rocket::async_trait]
+pub trait RigRepository: Send + Sync {
+    /// Every rig, or only the ones owned by `owner_id`
+    async fn list_rigs(&self, owner_id: Option<Uuid>, converter: &Converter) -> ApiResult<Vec<RigWithComponents>>;
+
+    /// Every rig, or only the ones owned by `owner_id`, with each of their
+    /// components, read as the stream is consumed. Rigs are ordered by name,
+    /// their components by slot and model; rows of a rig come one after the
+    /// other.
+    async fn stream_rig_components(&self, owner_id: Option<Uuid>) -> QueryResult<BoxStream<'static, Result<RigComponentRecord, Error>>>;
+
+    async fn get_rig(&self, id: Uuid, converter: &Converter) -> ApiResult<RigWithComponents>;
+
+    /// Prices the rig's current components with the last price each had by
+    /// the end of `at` (UTC). Past prices are converted at today's rates.
+    async fn get_rig_valuation(&self, id: Uuid, at: Date, converter: &Converter) -> ApiResult<RigValuation>;
+
+    /// The rig with its components as stored, unpriced
+    async fn get_rig_parts(&self, id: Uuid) -> QueryResult<(Rig, Vec<(RigComponent, Component)>)>;
+
+    async fn get_shared_rig(&self, slug: &str, converter: &Converter) -> ApiResult<RigWithComponents>;
+
+    async fn get_rig_owner(&self, id: Uuid) -> QueryResult<Option<Uuid>>;
+
+    /// Creates the rig if `check` passes, all in one transaction
+    async fn create_rig(&self, rig: Rig, rig_components: Vec<RigComponent>, check: ComponentsCheck) -> ApiResult<()>;
+
+    /// Reads the rig and creates the clone `fork` makes of it, all in one
+    /// transaction. `fork` gets the catalog with the rig's components and
+    /// `component_ids`.
+    async fn clone_rig(&self, id: Uuid, component_ids: &[Uuid], fork: Fork) -> ApiResult<Rig>;
+
+    async fn rename_rig(&self, id: Uuid, name: &str) -> QueryResult<Rig>;
+
+    /// Swaps every component of the rig for `rig_components` if `check`
+    /// passes, all in one transaction
+    async fn replace_rig_components(&self, id: Uuid, rig_components: Vec<RigComponent>, check: ComponentsCheck) -> ApiResult<()>;
+
+    /// Gives the rig a share slug, keeping the one it has if it's shared
+    /// already, or takes it away
+    async fn share_rig(&self, id: Uuid, shared: bool) -> QueryResult<Rig>;
+
+    async fn delete_rig(&self, id: Uuid) -> QueryResult<usize>;
+}
+
+#[rocket::async_trait]
+impl RigRepository for DieselRepository {
+    async fn list_rigs(&self, owner_id: Option<Uuid>, converter: &Converter) -> ApiResult<Vec<RigWithComponents>> {
+        list_rigs(owner_id, converter, &mut *self.connection().await?).await
+    }
+
+    async fn stream_rig_components(&self, owner_id: Option<Uuid>) -> QueryResult<BoxStream<'static, Result<RigComponentRecord, Error>>> {
+        let mut db = self.connection().await?;
+
+        Ok(stream! {
+            let mut rows = match stream_rig_components(owner_id, &mut db).await {
+                Ok(rows) => rows,
+                Err(err) => {
+                    yield Err(err.0);
+                    return;
+                },
+            };
+
+            while let Some(row) = rows.next().await {
+                yield row;
+            }
+        }.boxed())
+    }
+
+    async fn get_rig(&self, id: Uuid, converter: &Converter) -> ApiResult<RigWithComponents> {
+        get_rig(id, converter, &mut *self.connection().await?).await
+    }
+
+    async fn get_rig_valuation(&self, id: Uuid, at: Date, converter: &Converter) -> ApiResult<RigValuation> {
+        get_rig_valuation(id, at, converter, &mut *self.connection().await?).await
+    }
+
+    async fn get_rig_parts(&self, id: Uuid) -> QueryResult<(Rig, Vec<(RigComponent, Component)>)> {
+        get_rig_parts(id, &mut *self.connection().await?).await
+    }
 
-pub async fn list_rigs(db: &mut Connection<Db>) -> QueryResult<Vec<RigWithComponents>> {
-    let all_rigs = rigs::table
+    async fn get_shared_rig(&self, slug: &str, converter: &Converter) -> ApiResult<RigWithComponents> {
+        get_shared_rig(slug, converter, &mut *self.connection().await?).await
+    }
+
+    async fn get_rig_owner(&self, id: Uuid) -> QueryResult<Option<Uuid>> {
+        get_rig_owner(id, &mut *self.connection().await?).await
+    }
+
+    async fn create_rig(&self, rig: Rig, rig_components: Vec<RigComponent>, check: ComponentsCheck) -> ApiResult<()> {
+        self.connection().await?.transaction(|db| async move {
+            check(&component::load_catalog(&component_ids(&rig_components), db).await?)?;
+
+            create_rig(&rig, db).await?;
+            create_rig_components(&rig_components, db).await?;
+            revision::record_revision(rig.id, db).await?;
+            Ok::<_, ApiError>(())
+        }.scope_boxed()).await
+    }
+
+    async fn clone_rig(&self, id: Uuid, component_ids: &[Uuid], fork: Fork) -> ApiResult<Rig> {
+        self.connection().await?.transaction(|db| async move {
+            let (original, original_components) = get_rig_parts(id, db).await?;
+
+            let ids: Vec<Uuid> = original_components
+                .iter()
+                .map(|(rig_component, _)| rig_component.component_id)
+                .chain(component_ids.iter().copied())
+                .collect();
+            let catalog = component::load_catalog(&ids, db).await?;
+            let (clone, rig_components) = fork(original, original_components, &catalog)?;
+
+            create_rig(&clone, db).await?;
+            create_rig_components(&rig_components, db).await?;
+            revision::record_revision(clone.id, db).await?;
+            Ok::<_, ApiError>(clone)
+        }.scope_boxed()).await
+    }
+
+    async fn rename_rig(&self, id: Uuid, name: &str) -> QueryResult<Rig> {
+        self.connection().await?.transaction(|db| async move {
+            let rig = rename_rig(id, name, db).await?;
+            revision::record_revision(id, db).await?;
+            QueryResult::Ok(rig)
+        }.scope_boxed()).await
+    }
+
+    async fn replace_rig_components(&self, id: Uuid, rig_components: Vec<RigComponent>, check: ComponentsCheck) -> ApiResult<()> {
+        self.connection().await?.transaction(|db| async move {
+            // Fails with a 404 before touching the rig's components if it
+            // doesn't exist, and keeps concurrent changes from interleaving
+            lock_rig(id, db).await?;
+            check(&component::load_catalog(&component_ids(&rig_components), db).await?)?;
+
+            delete_rig_components(id, db).await?;
+            create_rig_components(&rig_components, db).await?;
+            revision::record_revision(id, db).await?;
+            Ok::<_, ApiError>(())
+        }.scope_boxed()).await
+    }
+
+    async fn share_rig(&self, id: Uuid, shared: bool) -> QueryResult<Rig> {
+        self.connection().await?.transaction(|db| async move {
+            let share_slug = match shared {
+                // Sharing again keeps links that are already out there working
+                true => Some(get_share_slug(id, db).await?.unwrap_or_else(generate_share_slug)),
+                false => None,
+            };
+
+            set_share_slug(id, share_slug, db).await
+        }.scope_boxed()).await
+    }
+
+    async fn delete_rig(&self, id: Uuid) -> QueryResult<usize> {
+        self.connection().await?.transaction(|db| async move {
+            delete_rig(id, db).await
+        }.scope_boxed()).await
+    }
+}
+
+fn component_ids(rig_components: &[RigComponent]) -> Vec<Uuid> {
+    rig_components
+        .iter()
+        .map(|rig_component| rig_component.component_id)
+        .collect()
+}
+
+/// Loads the rigs' components, priced in the converter's currency
+async fn load_components(rigs: Vec<Rig>, converter: &Converter, db: &mut AsyncPgConnection) -> ApiResult<Vec<RigWithComponents>> {
+    let rig_components = RigComponent::belonging_to(&rigs)
+        .inner_join(components::table)
+        .select((RigComponent::as_select(), Component::as_select()))
+        .load(db)
+        .await?;
+
+    rig_components
+        .grouped_by(&rigs)
+        .into_iter()
+        .zip(rigs)
+        .map(|(rc, rig)| {
+            let items = rc
+                .into_iter()
+                .map(|(rig_component, component)| Ok(RigComponentItem::new(component.converted(converter)?, rig_component.quantity)))
+                .collect::<ApiResult<_>>()?;
+
+            Ok(rig.with_components(items, converter.currency()))
+        })
+        .collect()
+}
+
+async fn list_rigs(owner_id: Option<Uuid>, converter: &Converter, db: &mut AsyncPgConnection) -> ApiResult<Vec<RigWithComponents>> {
+    let mut select = rigs::table.into_boxed();
+    if let Some(owner_id) = owner_id {
+        select = select.filter(rigs::owner_id.eq(owner_id));
+    }
+
+    let all_rigs = select
         .load::<Rig>(db)
         .await?;
 
-    let rig_components = RigComponent::belonging_to(&all_rigs)
+    load_components(all_rigs, converter, db).await
+}
+
+async fn stream_rig_components(owner_id: Option<Uuid>, db: &mut AsyncPgConnection) -> QueryResult<impl Stream<Item = Result<RigComponentRecord, Error>> + Send + '_> {
+    let mut select = rigs::table
+        .left_join(rig_components::table.inner_join(components::table))
+        .into_boxed();
+    if let Some(owner_id) = owner_id {
+        select = select.filter(rigs::owner_id.eq(owner_id));
+    }
+
+    Ok(select
+        .order((rigs::name, rigs::id, components::slot, components::model))
+        .select((Rig::as_select(), Option::<(RigComponent, Component)>::as_select()))
+        .load_stream(db)
+        .await?)
+}
+
+async fn get_rig(id: Uuid, converter: &Converter, db: &mut AsyncPgConnection) -> ApiResult<RigWithComponents> {
+    let rig = rigs::table
+        .find(id)
+        .get_result::<Rig>(db)
+        .await?;
+
+    let mut rigs = load_components(vec![rig], converter, db).await?;
+
+    Ok(rigs.remove(0))
+}
+
+async fn get_rig_valuation(id: Uuid, at: Date, converter: &Converter, db: &mut AsyncPgConnection) -> ApiResult<RigValuation> {
+    let until = at.next_day().map_or(PrimitiveDateTime::MAX, |day| day.midnight()).assume_utc();
+
+    let rig = rigs::table
+        .find(id)
+        .get_result::<Rig>(db)
+        .await?;
+
+    let rig_components = RigComponent::belonging_to(&rig)
         .inner_join(components::table)
         .select((RigComponent::as_select(), Component::as_select()))
-        .load(db)
+        .load::<(RigComponent, Component)>(db)
         .await?;
 
-    let rig_with_components: Vec<RigWithComponents> = rig_components
-        .grouped_by(&all_rigs)
+    let component_ids: Vec<Uuid> = rig_components
+        .iter()
+        .map(|(rig_component, _)| rig_component.component_id)
+        .collect();
+
+    let prices: HashMap<Uuid, Money> = component_prices::table
+        .filter(component_prices::component_id.eq_any(&component_ids))
+        .filter(component_prices::changed_at.lt(until))
+        .distinct_on(component_prices::component_id)
+        .order_by((component_prices::component_id, component_prices::changed_at.desc(), component_prices::id.desc()))
+        .select((component_prices::component_id, (component_prices::price_cents, component_prices::currency)))
+        .load::<(Uuid, Money)>(db)
+        .await?
         .into_iter()
-        .zip(all_rigs)
-        .map(|(rc, rig)| rig.with_components(
-            rc.into_iter().map(|(_, component)| component).collect()
-        ))
         .collect();
-    
-    Ok(rig_with_components)
+
+    let items = rig_components
+        .into_iter()
+        .map(|(rig_component, component)| {
+            let price = prices.get(&component.id).map(|price| converter.convert(price)).transpose()?;
+            Ok(ValuationItem::new(component.converted(converter)?, rig_component.quantity, price))
+        })
+        .collect::<ApiResult<_>>()?;
+
+    Ok(RigValuation::new(rig, at, items, converter.currency()))
+}
+
+async fn get_rig_parts(id: Uuid, db: &mut AsyncPgConnection) -> QueryResult<(Rig, Vec<(RigComponent, Component)>)> {
+    let rig = rigs::table
+        .find(id)
+        .get_result::<Rig>(db)
+        .await?;
+
+    let rig_components = RigComponent::belonging_to(&rig)
+        .inner_join(components::table)
+        .select((RigComponent::as_select(), Component::as_select()))
+        .load::<(RigComponent, Component)>(db)
+        .await?;
+
+    Ok((rig, rig_components))
+}
+
+async fn get_shared_rig(slug: &str, converter: &Converter, db: &mut AsyncPgConnection) -> ApiResult<RigWithComponents> {
+    let rig = rigs::table
+        .filter(rigs::share_slug.eq(slug))
+        .get_result::<Rig>(db)
+        .await?;
+
+    let mut rigs = load_components(vec![rig], converter, db).await?;
+
+    Ok(rigs.remove(0))
+}
+
+async fn get_rig_owner(id: Uuid, db: &mut AsyncPgConnection) -> QueryResult<Option<Uuid>> {
+    Ok(rigs::table
+        .find(id)
+        .select(rigs::owner_id)
+        .get_result(db)
+        .await?)
+}
+
+/// Reads the rig, locking its row until the transaction ends: changes to
+/// the rig's components take this lock first, so they run one at a time
+pub async fn lock_rig(id: Uuid, db: &mut AsyncPgConnection) -> QueryResult<Rig> {
+    Ok(rigs::table
+        .find(id)
+        .for_update()
+        .get_result::<Rig>(db)
+        .await?)
 }
 
-pub async fn create_rig(rig: &Rig, db: &mut Connection<Db>) -> QueryResult<usize> {
+async fn get_share_slug(id: Uuid, db: &mut AsyncPgConnection) -> QueryResult<Option<String>> {
+    Ok(rigs::table
+        .find(id)
+        .select(rigs::share_slug)
+        .get_result(db)
+        .await?)
+}
+
+async fn create_rig(rig: &Rig, db: &mut AsyncPgConnection) -> QueryResult<usize> {
     Ok(diesel::insert_into(rigs::table)
         .values(rig)
         .execute(db)
         .await?)
 }
 
-pub async fn create_rig_components(rig_components: &[RigComponent], db: &mut Connection<Db>) -> QueryResult<usize> {
+pub async fn rename_rig(id: Uuid, name: &str, db: &mut AsyncPgConnection) -> QueryResult<Rig> {
+    Ok(diesel::update(rigs::table.find(id))
+        .set(rigs::name.eq(name))
+        .get_result::<Rig>(db)
+        .await?)
+}
+
+async fn set_share_slug(id: Uuid, share_slug: Option<String>, db: &mut AsyncPgConnection) -> QueryResult<Rig> {
+    Ok(diesel::update(rigs::table.find(id))
+        .set(rigs::share_slug.eq(share_slug))
+        .get_result::<Rig>(db)
+        .await?)
+}
+
+pub async fn create_rig_components(rig_components: &[RigComponent], db: &mut AsyncPgConnection) -> QueryResult<usize> {
     Ok(diesel::insert_into(rig_components::table)
         .values(rig_components)
         .execute(db)
         .await?)
-}
\ No newline at end of file
+}
+
+pub async fn delete_rig_components(rig_id: Uuid, db: &mut AsyncPgConnection) -> QueryResult<usize> {
+    Ok(diesel::delete(rig_components::table.filter(rig_components::rig_id.eq(rig_id)))
+        .execute(db)
+        .await?)
+}
+
+async fn delete_rig(id: Uuid, db: &mut AsyncPgConnection) -> QueryResult<usize> {
+    delete_rig_components(id, db).await?;
+
+    Ok(diesel::delete(rigs::table.find(id))
+        .execute(db)
+        .await?)
+}
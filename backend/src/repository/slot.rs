@@ -0,0 +1,133 @@
+use api_types::{component::Component, schema::{components, slots}, slot::Slot};
+use rocket_db_pools::diesel::{
+    prelude::RunQueryDsl, scoped_futures::ScopedFutureExt, AsyncConnection, AsyncPgConnection, QueryResult, QueryDsl, ExpressionMethods,
+    SelectableHelper,
+};
+
+use super::DieselRepository;
+
+/// Where the routes read and write slots
+#[rocket::async_trait]
+pub trait SlotRepository: Send + Sync {
+    /// Every slot, in the order they're listed
+    async fn list_slots(&self) -> QueryResult<Vec<Slot>>;
+
+    async fn get_slot(&self, key: &str) -> QueryResult<Slot>;
+
+    async fn create_slot(&self, slot: Slot) -> QueryResult<Slot>;
+
+    async fn update_slot(&self, slot: Slot) -> QueryResult<Slot>;
+
+    /// Deletes the slot unless components fit in it, returning those
+    /// components instead. The slot stays locked from the check to the
+    /// delete, so no component can take it in between.
+    async fn delete_slot(&self, key: &str) -> QueryResult<Vec<Component>>;
+}
+
+#[rocket::async_trait]
+impl SlotRepository for DieselRepository {
+    async fn list_slots(&self) -> QueryResult<Vec<Slot>> {
+        list_slots(&mut *self.connection().await?).await
+    }
+
+    async fn get_slot(&self, key: &str) -> QueryResult<Slot> {
+        get_slot(key, &mut *self.connection().await?).await
+    }
+
+    async fn create_slot(&self, slot: Slot) -> QueryResult<Slot> {
+        create_slot(slot, &mut *self.connection().await?).await
+    }
+
+    async fn update_slot(&self, slot: Slot) -> QueryResult<Slot> {
+        update_slot(slot, &mut *self.connection().await?).await
+    }
+
+    async fn delete_slot(&self, key: &str) -> QueryResult<Vec<Component>> {
+        self.connection().await?.transaction(|db| async move {
+            // Components hold a key share lock on their slot, which waits
+            // for this one
+            lock_slot(key, db).await?;
+
+            let components = list_components_in_slot(key, db).await?;
+            if components.is_empty() {
+                delete_slot(key, db).await?;
+            }
+            QueryResult::Ok(components)
+        }.scope_boxed()).await
+    }
+}
+
+pub async fn list_slots(db: &mut AsyncPgConnection) -> QueryResult<Vec<Slot>> {
+    Ok(
+        slots::table
+            .order((slots::position, slots::key))
+            .load::<Slot>(db)
+            .await?
+    )
+}
+
+/// [`list_slots`], locked until the transaction ends
+pub async fn lock_slots(db: &mut AsyncPgConnection) -> QueryResult<Vec<Slot>> {
+    Ok(
+        slots::table
+            .order((slots::position, slots::key))
+            .for_share()
+            .load::<Slot>(db)
+            .await?
+    )
+}
+
+async fn get_slot(key: &str, db: &mut AsyncPgConnection) -> QueryResult<Slot> {
+    Ok(
+        slots::table
+            .find(key)
+            .get_result::<Slot>(db)
+            .await?
+    )
+}
+
+async fn lock_slot(key: &str, db: &mut AsyncPgConnection) -> QueryResult<Slot> {
+    Ok(
+        slots::table
+            .find(key)
+            .for_update()
+            .get_result::<Slot>(db)
+            .await?
+    )
+}
+
+async fn create_slot(slot: Slot, db: &mut AsyncPgConnection) -> QueryResult<Slot> {
+    Ok(
+        diesel::insert_into(slots::table)
+            .values(slot)
+            .get_result::<Slot>(db)
+            .await?
+    )
+}
+
+async fn update_slot(slot: Slot, db: &mut AsyncPgConnection) -> QueryResult<Slot> {
+    Ok(
+        diesel::update(&slot)
+            .set(&slot)
+            .get_result::<Slot>(db)
+            .await?
+    )
+}
+
+async fn delete_slot(key: &str, db: &mut AsyncPgConnection) -> QueryResult<usize> {
+    Ok(
+        diesel::delete(slots::table.find(key))
+            .execute(db)
+            .await?
+    )
+}
+
+async fn list_components_in_slot(key: &str, db: &mut AsyncPgConnection) -> QueryResult<Vec<Component>> {
+    Ok(
+        components::table
+            .filter(components::slot.eq(key))
+            .select(Component::as_select())
+            .load(db)
+            .await?
+    )
+}
@@ -0,0 +1,213 @@
+use api_types::{schema::{sessions, users}, types::Role, user::User};
+use rocket_db_pools::diesel::{
+    prelude::RunQueryDsl, scoped_futures::ScopedFutureExt, AsyncConnection, AsyncPgConnection, QueryResult, QueryDsl, ExpressionMethods,
+    SelectableHelper,
+};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::models::user::Session;
+
+use super::DieselRepository;
+
+/// Where requests look up who is behind their bearer token
+#[rocket::async_trait]
+pub trait SessionRepository: Send + Sync {
+    /// The user of an unexpired session
+    async fn get_session_user(&self, token_hash: &str) -> QueryResult<User>;
+}
+
+/// Where the routes read and write users, and start and end their sessions
+#[rocket::async_trait]
+pub trait UserRepository: Send + Sync {
+    async fn create_user(&self, user: User) -> QueryResult<User>;
+
+    /// Every user, ordered by email
+    async fn list_users(&self) -> QueryResult<Vec<User>>;
+
+    async fn get_user(&self, id: Uuid) -> QueryResult<User>;
+
+    async fn find_user_by_email(&self, email: &str) -> QueryResult<User>;
+
+    /// Gives the user `role`, or nothing when that would leave no admin
+    async fn set_role(&self, id: Uuid, role: Role) -> QueryResult<Option<User>>;
+
+    async fn create_session(&self, session: Session) -> QueryResult<Session>;
+
+    async fn delete_session(&self, token_hash: &str) -> QueryResult<usize>;
+
+    async fn delete_expired_sessions(&self) -> QueryResult<usize>;
+}
+
+#[rocket::async_trait]
+impl SessionRepository for DieselRepository {
+    async fn get_session_user(&self, token_hash: &str) -> QueryResult<User> {
+        get_session_user(token_hash, &mut *self.connection().await?).await
+    }
+}
+
+#[rocket::async_trait]
+impl UserRepository for DieselRepository {
+    async fn create_user(&self, user: User) -> QueryResult<User> {
+        create_user(user, &mut *self.connection().await?).await
+    }
+
+    async fn list_users(&self) -> QueryResult<Vec<User>> {
+        list_users(&mut *self.connection().await?).await
+    }
+
+    async fn get_user(&self, id: Uuid) -> QueryResult<User> {
+        get_user(id, &mut *self.connection().await?).await
+    }
+
+    async fn find_user_by_email(&self, email: &str) -> QueryResult<User> {
+        find_user_by_email(email, &mut *self.connection().await?).await
+    }
+
+    async fn set_role(&self, id: Uuid, role: Role) -> QueryResult<Option<User>> {
+        self.connection().await?.transaction(|db| async move {
+            // Concurrent demotions wait here, then only see the admins left
+            let admins = lock_admins(db).await?;
+            if role != Role::Admin && admins == [id] {
+                return QueryResult::Ok(None);
+            }
+
+            set_role(id, role, db).await.map(Some)
+        }.scope_boxed()).await
+    }
+
+    async fn create_session(&self, session: Session) -> QueryResult<Session> {
+        create_session(session, &mut *self.connection().await?).await
+    }
+
+    async fn delete_session(&self, token_hash: &str) -> QueryResult<usize> {
+        delete_session(token_hash, &mut *self.connection().await?).await
+    }
+
+    async fn delete_expired_sessions(&self) -> QueryResult<usize> {
+        delete_expired_sessions(&mut *self.connection().await?).await
+    }
+}
+
+async fn create_user(user: User, db: &mut AsyncPgConnection) -> QueryResult<User> {
+    Ok(
+        diesel::insert_into(users::table)
+            .values(user)
+            .returning(User::as_returning())
+            .get_result(db)
+            .await?
+    )
+}
+
+async fn list_users(db: &mut AsyncPgConnection) -> QueryResult<Vec<User>> {
+    Ok(
+        users::table
+            .order(users::email)
+            .select(User::as_select())
+            .load(db)
+            .await?
+    )
+}
+
+async fn get_user(id: Uuid, db: &mut AsyncPgConnection) -> QueryResult<User> {
+    Ok(
+        users::table
+            .find(id)
+            .select(User::as_select())
+            .get_result(db)
+            .await?
+    )
+}
+
+async fn lock_admins(db: &mut AsyncPgConnection) -> QueryResult<Vec<Uuid>> {
+    Ok(
+        users::table
+            .filter(users::role.eq(Role::Admin))
+            .select(users::id)
+            .for_update()
+            .load(db)
+            .await?
+    )
+}
+
+async fn set_role(id: Uuid, role: Role, db: &mut AsyncPgConnection) -> QueryResult<User> {
+    Ok(
+        diesel::update(users::table.find(id))
+            .set(users::role.eq(role))
+            .returning(User::as_returning())
+            .get_result(db)
+            .await?
+    )
+}
+
+/// Takes a plain connection since it also runs at launch, outside of requests
+pub async fn count_admins(db: &mut AsyncPgConnection) -> QueryResult<i64> {
+    Ok(
+        users::table
+            .filter(users::role.eq(Role::Admin))
+            .count()
+            .get_result(db)
+            .await?
+    )
+}
+
+/// Creates `user` as an admin. Fails with a unique violation when its email
+/// is taken, as whoever registered it must not be promoted.
+pub async fn create_admin(user: User, db: &mut AsyncPgConnection) -> QueryResult<User> {
+    Ok(
+        diesel::insert_into(users::table)
+            .values(User { role: Role::Admin, ..user })
+            .returning(User::as_returning())
+            .get_result(db)
+            .await?
+    )
+}
+
+async fn find_user_by_email(email: &str, db: &mut AsyncPgConnection) -> QueryResult<User> {
+    Ok(
+        users::table
+            .filter(users::email.eq(email))
+            .select(User::as_select())
+            .get_result(db)
+            .await?
+    )
+}
+
+async fn create_session(session: Session, db: &mut AsyncPgConnection) -> QueryResult<Session> {
+    Ok(
+        diesel::insert_into(sessions::table)
+            .values(session)
+            .returning(Session::as_returning())
+            .get_result(db)
+            .await?
+    )
+}
+
+/// The user of an unexpired session
+async fn get_session_user(token_hash: &str, db: &mut AsyncPgConnection) -> QueryResult<User> {
+    Ok(
+        sessions::table
+            .inner_join(users::table)
+            .filter(sessions::token_hash.eq(token_hash))
+            .filter(sessions::expires_at.gt(OffsetDateTime::now_utc()))
+            .select(User::as_select())
+            .get_result(db)
+            .await?
+    )
+}
+
+async fn delete_session(token_hash: &str, db: &mut AsyncPgConnection) -> QueryResult<usize> {
+    Ok(
+        diesel::delete(sessions::table.find(token_hash))
+            .execute(db)
+            .await?
+    )
+}
+
+async fn delete_expired_sessions(db: &mut AsyncPgConnection) -> QueryResult<usize> {
+    Ok(
+        diesel::delete(sessions::table.filter(sessions::expires_at.le(OffsetDateTime::now_utc())))
+            .execute(db)
+            .await?
+    )
+}
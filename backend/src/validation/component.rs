@@ -0,0 +1,75 @@
+use std::collections::HashSet;
+
+use api_types::{component::{ComponentQuery, CreateComponentData}, money::Money, page::MAX_PER_PAGE};
+
+use super::ValidationErrors;
+
+pub fn validate_query(query: &ComponentQuery, errors: &mut ValidationErrors) {
+    for (field, price) in [("min_price", query.min_price), ("max_price", query.max_price)] {
+        if price.is_some_and(|price| price < 0) {
+            errors.add(field, "must not be negative");
+        }
+    }
+
+    if let (Some(min_price), Some(max_price)) = (query.min_price, query.max_price) {
+        if min_price > max_price {
+            errors.add("min_price", "must not be greater than max_price");
+        }
+    }
+
+    if let Some(currency) = &query.currency {
+        super::currency::validate_code(currency, "currency", errors);
+    }
+
+    super::page::validate_page(query.pagination(), errors);
+}
+
+/// Highest price a component can have, in cents. Keeps rig totals far from
+/// overflowing, even converted into a weaker currency.
+pub const MAX_PRICE: i64 = 10_000_000_000;
+
+pub fn validate_price(price: &Money, errors: &mut ValidationErrors) {
+    check_amount(price.amount, errors);
+
+    super::currency::validate_code(&price.currency, "price.currency", errors);
+}
+
+/// Checks an imported component up front, including what the foreign keys
+/// would otherwise reject, so one bad row doesn't fail the whole import
+pub fn validate_import(component: &CreateComponentData, slots: &HashSet<String>, currencies: &HashSet<String>, errors: &mut ValidationErrors) {
+    for (field, value) in [("manufacturer", &component.manufacturer), ("model", &component.model)] {
+        if value.trim().is_empty() {
+            errors.add(field, "must not be empty");
+        }
+    }
+
+    if !slots.contains(&component.slot) {
+        errors.add("slot", format!("slot {} does not exist", component.slot));
+    }
+
+    check_amount(component.price.amount, errors);
+
+    if !currencies.contains(&component.price.currency) {
+        errors.add("price.currency", format!("currency {} has no exchange rate", component.price.currency));
+    }
+}
+
+fn check_amount(amount: i64, errors: &mut ValidationErrors) {
+    if amount < 0 {
+        errors.add("price.amount", "must not be negative");
+    }
+
+    if amount > MAX_PRICE {
+        errors.add("price.amount", format!("must be at most {MAX_PRICE}"));
+    }
+}
+
+pub fn validate_search(terms: &str, limit: i64, errors: &mut ValidationErrors) {
+    if terms.trim().is_empty() {
+        errors.add("q", "must not be empty");
+    }
+
+    if !(1..=MAX_PER_PAGE).contains(&limit) {
+        errors.add("limit", format!("must be between 1 and {MAX_PER_PAGE}"));
+    }
+}
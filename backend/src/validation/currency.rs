@@ -0,0 +1,19 @@
+use crate::money::{BASE_CURRENCY, RATE_SCALE};
+
+use super::ValidationErrors;
+
+pub fn validate_code(code: &str, field: &str, errors: &mut ValidationErrors) {
+    if code.len() != 3 || !code.chars().all(|c| c.is_ascii_uppercase()) {
+        errors.add(field, "must be an ISO 4217 code, e.g. BRL");
+    }
+}
+
+pub fn validate_rate(currency: &str, rate_micros: i64, errors: &mut ValidationErrors) {
+    if rate_micros <= 0 {
+        errors.add("rate_micros", "must be positive");
+    }
+
+    if currency == BASE_CURRENCY && rate_micros != RATE_SCALE {
+        errors.add("rate_micros", format!("must be {RATE_SCALE} for the base currency {BASE_CURRENCY}"));
+    }
+}
@@ -0,0 +1,53 @@
+use api_types::error::FieldError;
+use rocket::form;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+pub mod component;
+pub mod currency;
+pub mod page;
+pub mod rig;
+pub mod slot;
+pub mod user;
+
+#[derive(Serialize, Debug, Default, ToSchema)]
+pub struct ValidationErrors {
+    pub fields: Vec<FieldError>,
+}
+
+impl ValidationErrors {
+    pub fn add(&mut self, field: impl Into<String>, message: impl Into<String>) {
+        self.fields.push(FieldError {
+            field: field.into(),
+            message: message.into(),
+        });
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    pub fn into_result(self) -> Result<(), Self> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl From<form::Errors<'_>> for ValidationErrors {
+    fn from(value: form::Errors<'_>) -> Self {
+        let mut errors = Self::default();
+        for error in value.iter() {
+            let field = error.name
+                .as_ref()
+                .map(|name| name.to_string())
+                .unwrap_or_default();
+
+            errors.add(field, error.kind.to_string());
+        }
+
+        errors
+    }
+}
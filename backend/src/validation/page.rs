@@ -0,0 +1,13 @@
+use api_types::page::{PageQuery, MAX_PAGE, MAX_PER_PAGE};
+
+use super::ValidationErrors;
+
+pub fn validate_page(query: PageQuery, errors: &mut ValidationErrors) {
+    if !(1..=MAX_PAGE).contains(&query.page) {
+        errors.add("page", format!("must be between 1 and {MAX_PAGE}"));
+    }
+
+    if !(1..=MAX_PER_PAGE).contains(&query.per_page) {
+        errors.add("per_page", format!("must be between 1 and {MAX_PER_PAGE}"));
+    }
+}
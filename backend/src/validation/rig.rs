@@ -0,0 +1,103 @@
+use std::collections::{HashMap, HashSet};
+
+use api_types::{component::Component, money::Money, rig::RigComponentData, slot::Slot};
+use uuid::Uuid;
+
+use super::ValidationErrors;
+
+/// Most units of one component a rig can hold
+pub const MAX_QUANTITY: i32 = 1_000;
+
+pub fn validate_name(name: &str, errors: &mut ValidationErrors) {
+    if name.trim().is_empty() {
+        errors.add("name", "must not be empty");
+    }
+}
+
+/// What a rig's components are checked against: the components of the
+/// catalog they may point to, and every slot
+pub struct Catalog {
+    pub components: Vec<Component>,
+    pub slots: Vec<Slot>,
+}
+
+impl Catalog {
+    /// The catalog's components among `ids`
+    pub fn components_of(&self, ids: &[Uuid]) -> Vec<Component> {
+        self.components
+            .iter()
+            .filter(|component| ids.contains(&component.id))
+            .cloned()
+            .collect()
+    }
+}
+
+/// Checks that every id points to an existing component, that no id is
+/// repeated, that quantities are between 1 and [`MAX_QUANTITY`], that the
+/// rig's prices add up without overflowing and that the rig respects the
+/// cardinality of every slot, counting each unit of a component. `catalog`
+/// is meant to be read in the transaction that writes the components.
+pub fn check_components(components: &[RigComponentData], errors: &mut ValidationErrors, catalog: &Catalog) {
+    let known: HashMap<Uuid, &Component> = catalog.components
+        .iter()
+        .map(|component| (component.id, component))
+        .collect();
+
+    let mut seen = HashSet::new();
+    let mut slots: HashMap<&str, i32> = HashMap::new();
+    // Line totals by currency, as prices are only converted when shown
+    let mut totals: HashMap<&str, Vec<Money>> = HashMap::new();
+
+    for (index, RigComponentData { component_id: id, quantity }) in components.iter().enumerate() {
+        let field = format!("components[{index}]");
+
+        if !seen.insert(id) {
+            errors.add(field, format!("component {id} is listed more than once"));
+            continue;
+        }
+
+        if *quantity < 1 {
+            errors.add(format!("{field}.quantity"), "must be at least 1");
+            continue;
+        }
+
+        if *quantity > MAX_QUANTITY {
+            errors.add(format!("{field}.quantity"), format!("must be at most {MAX_QUANTITY}"));
+            continue;
+        }
+
+        let Some(component) = known.get(id) else {
+            errors.add(field, format!("component {id} does not exist"));
+            continue;
+        };
+
+        let count = slots.entry(component.slot.as_str()).or_default();
+        match count.checked_add(*quantity) {
+            Some(sum) => *count = sum,
+            None => errors.add("components", format!("too many {} components", component.slot)),
+        }
+
+        match component.price.times(*quantity) {
+            Some(line_total) => totals.entry(component.price.currency.as_str()).or_default().push(line_total),
+            None => errors.add(format!("{field}.quantity"), "makes the price too large"),
+        }
+    }
+
+    for (currency, line_totals) in &totals {
+        if Money::sum(currency, line_totals).is_none() {
+            errors.add("components", format!("total price in {currency} is too large"));
+        }
+    }
+
+    for slot in &catalog.slots {
+        let count = slots.get(slot.key.as_str()).copied().unwrap_or_default();
+
+        if count < slot.min_per_rig {
+            errors.add("components", format!("at least {} {} component(s) required, got {count}", slot.min_per_rig, slot.name));
+        }
+
+        if let Some(max_per_rig) = slot.max_per_rig.filter(|max| count > *max) {
+            errors.add("components", format!("at most {max_per_rig} {} component(s) allowed, got {count}", slot.name));
+        }
+    }
+}
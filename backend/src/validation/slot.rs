@@ -0,0 +1,32 @@
+use api_types::slot::UpdateSlotData;
+
+use super::ValidationErrors;
+
+pub fn validate_key(key: &str, errors: &mut ValidationErrors) {
+    let is_valid = !key.is_empty()
+        && key.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-');
+
+    if !is_valid {
+        errors.add("key", "must only contain lowercase letters, digits, '-' and '_'");
+    }
+}
+
+pub fn validate_slot(slot: &UpdateSlotData, errors: &mut ValidationErrors) {
+    if slot.name.trim().is_empty() {
+        errors.add("name", "must not be empty");
+    }
+
+    if slot.icon.trim().is_empty() {
+        errors.add("icon", "must not be empty");
+    }
+
+    if slot.min_per_rig < 0 {
+        errors.add("min_per_rig", "must not be negative");
+    }
+
+    if let Some(max_per_rig) = slot.max_per_rig {
+        if max_per_rig < slot.min_per_rig.max(1) {
+            errors.add("max_per_rig", "must be at least 1 and not less than min_per_rig");
+        }
+    }
+}
@@ -0,0 +1,20 @@
+use super::ValidationErrors;
+
+pub const MIN_PASSWORD_LENGTH: usize = 8;
+/// Bounds the work a single request can ask argon2 for
+pub const MAX_PASSWORD_LENGTH: usize = 256;
+
+pub fn validate_credentials(email: &str, password: &str, errors: &mut ValidationErrors) {
+    let is_email = email
+        .split_once('@')
+        .is_some_and(|(user, domain)| !user.is_empty() && domain.contains('.') && !domain.contains('@'));
+
+    if !is_email {
+        errors.add("email", "must be an email address");
+    }
+
+    let length = password.chars().count();
+    if !(MIN_PASSWORD_LENGTH..=MAX_PASSWORD_LENGTH).contains(&length) {
+        errors.add("password", format!("must have between {MIN_PASSWORD_LENGTH} and {MAX_PASSWORD_LENGTH} characters"));
+    }
+}
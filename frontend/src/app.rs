@@ -2,11 +2,37 @@ use leptos::*;
 use leptos_meta::*;
 use leptos_router::*;
 
+use crate::{
+    pages::user::SessionBar,
+    services::{slot::fetch_slots, user::fetch_current_user},
+    types::{slot::SlotsResource, user::CurrentUserResource},
+};
+
 #[component]
 pub fn App() -> impl IntoView {
     // Provides context that manages stylesheets, titles, meta tags, etc.
     provide_meta_context();
 
+    let slots: SlotsResource = create_resource(|| (), |_| async {
+        fetch_slots()
+            .await
+            .unwrap_or_else(|err| {
+                logging::error!("Fetch Slots Error: {err}");
+                Vec::new()
+            })
+    });
+    provide_context(slots);
+
+    let current_user: CurrentUserResource = create_resource(|| (), |_| async {
+        fetch_current_user()
+            .await
+            .unwrap_or_else(|err| {
+                logging::error!("Fetch Current User Error: {err}");
+                None
+            })
+    });
+    provide_context(current_user);
+
     view! {
         // injects a stylesheet into the document <head>
         // id=leptos means cargo-leptos will hot-reload this stylesheet
@@ -17,11 +43,16 @@ pub fn App() -> impl IntoView {
 
         // content for this welcome page
         <Router>
+            <SessionBar />
             <main>
                 <Routes>
                     <Route path="" view=HomePage/>
                     <Route path="/components" view=crate::pages::component::ComponentsPage />
                     <Route path="/rigs" view=crate::pages::rig::RigsPage />
+                    <Route path="/login" view=crate::pages::user::LoginPage />
+                    // Rendered completely on the server so shared links
+                    // show the rig without waiting on the client
+                    <Route path="/r/:slug" view=crate::pages::rig::SharedRigPage ssr=SsrMode::Async />
                     <Route path="/*any" view=NotFound/>
                 </Routes>
             </main>
@@ -44,7 +75,7 @@ fn HomePage() -> impl IntoView {
 
 /// 404 - Not Found
 #[component]
-fn NotFound() -> impl IntoView {
+pub fn NotFound() -> impl IntoView {
     // set an HTTP status code 404
     // this is feature gated because it can only be done during
     // initial server-side rendering
@@ -0,0 +1,125 @@
+//! Where and how server functions reach the backend.
+//!
+//! Defaults come from `[package.metadata.leptos]` in this crate's
+//! `Cargo.toml`, read when the server is compiled; environment variables set
+//! when it starts override them, so one build can run against any backend:
+//!
+//! | Metadata key            | Environment variable    |
+//! |-------------------------|-------------------------|
+//! | `backend-url`           | `BACKEND_URL`           |
+//! | `backend-timeout-ms`    | `BACKEND_TIMEOUT_MS`    |
+//! | `artificial-latency-ms` | `ARTIFICIAL_LATENCY_MS` |
+
+use std::{env, fmt::Display, time::Duration};
+
+use api_client::RigApiClient;
+use serde::Deserialize;
+
+const MANIFEST: &str = include_str!("../Cargo.toml");
+
+const DEFAULT_BACKEND_URL: &str = "http://localhost:8000";
+const DEFAULT_BACKEND_TIMEOUT_MS: u64 = 10_000;
+
+/// Provided to every render and server function by `main`, read it with
+/// `expect_context::<FrontendConfig>()`
+#[derive(Clone, Debug)]
+pub struct FrontendConfig {
+    pub backend_url: String,
+    /// How long a request to the backend may take before it fails
+    pub backend_timeout: Duration,
+    /// Waited before every request to the backend, to show off loading
+    /// states in demos
+    pub artificial_latency: Option<Duration>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+struct Metadata {
+    backend_url: Option<String>,
+    backend_timeout_ms: Option<u64>,
+    artificial_latency_ms: Option<u64>,
+}
+
+#[derive(Debug)]
+pub struct ConfigError {
+    pub key: &'static str,
+    pub message: String,
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.key, self.message)
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl FrontendConfig {
+    pub fn load() -> Result<Self, ConfigError> {
+        let metadata = manifest_metadata()?;
+
+        let backend_url = env_var("BACKEND_URL", |value| Ok(value.to_owned()))?
+            .or(metadata.backend_url)
+            .unwrap_or_else(|| DEFAULT_BACKEND_URL.to_owned());
+        let backend_timeout_ms = env_var("BACKEND_TIMEOUT_MS", parse_millis)?
+            .or(metadata.backend_timeout_ms)
+            .unwrap_or(DEFAULT_BACKEND_TIMEOUT_MS);
+        let artificial_latency_ms = env_var("ARTIFICIAL_LATENCY_MS", parse_millis)?
+            .or(metadata.artificial_latency_ms)
+            .unwrap_or_default();
+
+        Ok(Self {
+            backend_url,
+            backend_timeout: Duration::from_millis(backend_timeout_ms),
+            artificial_latency: (artificial_latency_ms > 0).then(|| Duration::from_millis(artificial_latency_ms)),
+        })
+    }
+
+    pub fn api_client(&self) -> RigApiClient {
+        RigApiClient::new(self.backend_url.clone()).with_timeout(self.backend_timeout)
+    }
+}
+
+fn manifest_metadata() -> Result<Metadata, ConfigError> {
+    #[derive(Deserialize)]
+    struct Manifest {
+        package: Package,
+    }
+
+    #[derive(Deserialize)]
+    struct Package {
+        #[serde(default)]
+        metadata: PackageMetadata,
+    }
+
+    #[derive(Deserialize, Default)]
+    struct PackageMetadata {
+        #[serde(default)]
+        leptos: Metadata,
+    }
+
+    toml::from_str::<Manifest>(MANIFEST)
+        .map(|manifest| manifest.package.metadata.leptos)
+        .map_err(|err| ConfigError {
+            key: "package.metadata.leptos",
+            message: err.to_string(),
+        })
+}
+
+/// The variable `key` read with `parse`, `None` when it isn't set
+fn env_var<T>(key: &'static str, parse: impl Fn(&str) -> Result<T, String>) -> Result<Option<T>, ConfigError> {
+    match env::var(key) {
+        Ok(value) => parse(&value)
+            .map(Some)
+            .map_err(|message| ConfigError { key, message }),
+        Err(env::VarError::NotPresent) => Ok(None),
+        Err(err) => Err(ConfigError { key, message: err.to_string() }),
+    }
+}
+
+fn parse_millis(value: &str) -> Result<u64, String> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| format!("expected a number of milliseconds, got {value:?}"))
+}
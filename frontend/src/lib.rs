@@ -1,6 +1,8 @@
 #![feature(async_closure)]
 
 pub mod app;
+#[cfg(feature = "ssr")]
+pub mod config;
 pub mod types;
 pub mod pages;
 pub mod services;
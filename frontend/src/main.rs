@@ -5,11 +5,12 @@
 async fn main() -> std::io::Result<()> {
     use actix_files::Files;
     use actix_web::*;
-    use frontend::app::*;
+    use frontend::{app::*, config::FrontendConfig};
     use leptos::*;
     use leptos_actix::{generate_route_list, LeptosRoutes};
 
     let conf = get_configuration(None).await.unwrap();
+    let frontend_config = FrontendConfig::load().unwrap_or_else(|err| panic!("invalid frontend configuration: {err}"));
     let addr = conf.leptos_options.site_addr;
     // Generate the list of routes in your Leptos App
     let routes = generate_route_list(App);
@@ -18,6 +19,7 @@ async fn main() -> std::io::Result<()> {
     HttpServer::new(move || {
         let leptos_options = &conf.leptos_options;
         let site_root = &leptos_options.site_root;
+        let frontend_config = frontend_config.clone();
 
         App::new()
             // serve JS/WASM/CSS from `pkg`
@@ -26,7 +28,13 @@ async fn main() -> std::io::Result<()> {
             .service(Files::new("/assets", site_root))
             // serve the favicon from /favicon.ico
             .service(favicon)
-            .leptos_routes(leptos_options.to_owned(), routes.to_owned(), App)
+            // Server functions are registered here too, with the same context
+            .leptos_routes_with_context(
+                leptos_options.to_owned(),
+                routes.to_owned(),
+                move || provide_context(frontend_config.clone()),
+                App,
+            )
             .app_data(web::Data::new(leptos_options.to_owned()))
         //.wrap(middleware::Compress::default())
     })
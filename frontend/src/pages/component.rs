@@ -1,49 +1,129 @@
+use std::collections::HashMap;
+
+use api_types::{component::{Component, CreateComponentData}, money::Money, types::Role};
 use leptos::*;
+use leptos_meta::Style;
+use leptos_use::signal_debounced;
 use thaw::*;
-use strum::VariantArray;
+use uuid::Uuid;
 
-use crate::{services::component::{create_component, fetch_components}, types::component::{ComponentData, CreateComponentData, Slot}};
+use crate::{
+    services::component::{create_component, fetch_components, search_components},
+    types::{money::DISPLAY_CURRENCY, slot::SlotsResource, user::CurrentUserResource},
+};
 
 #[component]
 pub fn ComponentsPage() -> impl IntoView {
-    let loaded = create_rw_signal(false);
-    let (components, set_components) = create_signal(Vec::new());
+    let page = create_rw_signal(1);
+    let components_page = create_resource(page, fetch_components);
 
-    let components_loader = Resource::once(move || async {
-        fetch_components().await
+    create_effect(move |_| {
+        if let Some(Err(err)) = components_page.get() {
+            logging::error!("Fetch Components Error: {err}");
+        }
     });
 
-    create_effect(move |_| {
-        components_loader.and_then(|components_data| {
-            logging::log!("{components_data:?}");
-            set_components.update(|components| components.extend(components_data.iter().cloned()));
-            loaded.set(true);
-        })
+    let loaded = Signal::derive(move || components_page.with(|page| matches!(page, Some(Ok(_)))));
+    let total_pages = Signal::derive(move || match components_page.get() {
+        Some(Ok(page)) => page.total_pages,
+        _ => 1,
     });
 
-    let (read_component, write_component) = create_signal(Option::<ComponentData>::None);
+    let (read_component, write_component) = create_signal(Option::<Component>::None);
 
+    // A new component may land on any page, so the current one is reloaded
     create_effect(move |_| {
-        if let Some(component_data) = read_component() {
-            set_components.update(|components| components.push(component_data));
+        if read_component().is_some() {
+            components_page.refetch();
+        }
+    });
+
+    let search = create_rw_signal(String::new());
+    let search_query = signal_debounced(search, 300.0);
+
+    let search_results = create_resource(search_query, |query| async move {
+        if query.trim().is_empty() {
+            Ok(None)
+        } else {
+            search_components(query).await.map(Some)
         }
     });
 
+    let shown_components = Signal::derive(move || match (search_results.get(), components_page.get()) {
+        (Some(Ok(Some(results))), _) => results,
+        (_, Some(Ok(page))) => page.items,
+        _ => Vec::new(),
+    });
+    let searching = Signal::derive(move || matches!(search_results.get(), Some(Ok(Some(_)))));
+
     let show_add_modal = create_rw_signal(false);
+    // Only admins may add to the catalog
+    let current_user = expect_context::<CurrentUserResource>();
+    let can_add = Signal::derive(move || current_user.get().flatten().is_some_and(|user| user.role == Role::Admin));
 
     view! {
-        <ComponentsTable components loaded/>
-        <Button block=true on_click=move |_| show_add_modal.set(true) >Add</Button>
+        <Input value=search placeholder="Search by manufacturer or model" />
+        <ComponentsTable components=shown_components loaded/>
+        <Show when=move || !searching()>
+            <Pager page total_pages />
+        </Show>
+        <Show when=can_add>
+            <Button block=true on_click=move |_| show_add_modal.set(true) >Add</Button>
+        </Show>
         <AddComponentModal show=show_add_modal write_component />
     }
 }
 
+/// Previous and next buttons over pages numbered from 1
+#[component]
+fn Pager(
+    page: RwSignal<i64>,
+    #[prop(into)] total_pages: Signal<i64>,
+) -> impl IntoView {
+    let is_first = Signal::derive(move || page() <= 1);
+    let is_last = Signal::derive(move || page() >= total_pages());
+
+    view! {
+        <Grid cols=3>
+            <GridItem>
+                <Button block=true disabled=is_first on_click=move |_| page.update(|page| *page -= 1)>"Previous"</Button>
+            </GridItem>
+            <GridItem>
+                <div class="page">
+                    {move || format!("Page {} of {}", page(), total_pages().max(1))}
+                </div>
+                <Style>
+                ".page {
+                    height: 32px;
+                    display: flex;
+                    align-items: center;
+                    justify-content: center;
+                }"
+                </Style>
+            </GridItem>
+            <GridItem>
+                <Button block=true disabled=is_last on_click=move |_| page.update(|page| *page += 1)>"Next"</Button>
+            </GridItem>
+        </Grid>
+    }
+}
+
 #[component]
 pub fn ComponentsTable(
-    #[prop(into)] components: MaybeSignal<Vec<ComponentData>>,
+    #[prop(into)] components: MaybeSignal<Vec<Component>>,
     #[prop(into)] loaded: MaybeSignal<bool>,
+    /// Units of each component by id. When given, quantity and line total
+    /// columns are shown.
+    #[prop(optional, into)] quantities: Option<MaybeSignal<HashMap<Uuid, i32>>>,
 ) -> impl IntoView {
     let rows = Signal::derive(components);
+    let show_quantities = quantities.is_some();
+    let quantity_of = move |id: &Uuid| {
+        quantities
+            .as_ref()
+            .and_then(|quantities| quantities.with(|quantities| quantities.get(id).copied()))
+            .unwrap_or(1)
+    };
 
     view! {
         <Table>
@@ -52,7 +132,11 @@ pub fn ComponentsTable(
                     <th>"Slot"</th>
                     <th>"Manufacturer"</th>
                     <th>"Model"</th>
-                    <th>"Price (R$)"</th>
+                    <th>"Price"</th>
+                    <Show when=move || show_quantities>
+                        <th>"Qty"</th>
+                        <th>"Total"</th>
+                    </Show>
                 </tr>
             </thead>
 
@@ -69,15 +153,25 @@ pub fn ComponentsTable(
                 >
                     <For
                         each=rows
-                        key=|component| component.id.clone()
+                        key=|component| component.id
                         let:component
                     >
-                        <tr>
-                            <td>{component.slot}</td>
-                            <td>{component.manufacturer}</td>
-                            <td>{component.model}</td>
-                            <td>{component.price}</td>
-                        </tr>
+                        {
+                            let quantity = quantity_of(&component.id);
+                            let line_total = component.price.saturating_times(quantity).to_string();
+                            view! {
+                                <tr>
+                                    <td><SlotLabel slot=component.slot /></td>
+                                    <td>{component.manufacturer}</td>
+                                    <td>{component.model}</td>
+                                    <td>{component.price.to_string()}</td>
+                                    <Show when=move || show_quantities>
+                                        <td>{quantity}</td>
+                                        <td>{line_total}</td>
+                                    </Show>
+                                </tr>
+                            }
+                        }
                     </For>
                 </Show>
             </tbody>
@@ -85,24 +179,47 @@ pub fn ComponentsTable(
     }
 }
 
+/// Shows the icon of the slot with the given key, or the key itself while
+/// slots are loading.
+#[component]
+pub fn SlotLabel(
+    #[prop(into)] slot: String,
+) -> impl IntoView {
+    let slots = expect_context::<SlotsResource>();
+
+    move || {
+        slots.get()
+            .unwrap_or_default()
+            .into_iter()
+            .find(|candidate| candidate.key == slot)
+            .map(IntoView::into_view)
+            .unwrap_or_else(|| slot.clone().into_view())
+    }
+}
+
 #[component]
 fn AddComponentModal(
     #[prop(into)] show: RwSignal<bool>,
-    #[prop(into)] write_component: WriteSignal<Option<ComponentData>>,
+    #[prop(into)] write_component: WriteSignal<Option<Component>>,
 ) -> impl IntoView {
     let manufacturer = create_rw_signal(String::new());
     let model = create_rw_signal(String::new());
-    let price = create_rw_signal(0);
+    // In whole units of the display currency, sent to the backend in cents
+    let price = create_rw_signal(0.0);
 
-    let slot_options: Vec<_> = Slot::VARIANTS
-        .iter()
-        .map(|slot| SelectOption {
-            label: slot.to_string(),
-            value: *slot,
-        })
-        .collect();
+    let slots = expect_context::<SlotsResource>();
+    let slot_options = Signal::derive(move || {
+        slots.get()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|slot| SelectOption {
+                label: slot.name,
+                value: slot.key,
+            })
+            .collect::<Vec<_>>()
+    });
 
-    let slot = create_rw_signal(Option::<Slot>::None);
+    let slot = create_rw_signal(Option::<String>::None);
 
     let manufacturer_invalid = Signal::derive(move || manufacturer().is_empty());
     let model_invalid = Signal::derive(move || model().is_empty());
@@ -113,6 +230,7 @@ fn AddComponentModal(
         is_submitting()
         || manufacturer_invalid()
         || model_invalid()
+        || price() < 0.0
         || slot().is_none()
     );
 
@@ -123,7 +241,10 @@ fn AddComponentModal(
             manufacturer: manufacturer(),
             model: model(),
             slot: slot().unwrap(),
-            price: price(),
+            price: Money::new((price() * 100.0).round() as i64, DISPLAY_CURRENCY),
+            socket: None,
+            memory_generations: Vec::new(),
+            interfaces: Vec::new(),
         };
 
         spawn_local(async move {
@@ -134,7 +255,7 @@ fn AddComponentModal(
                     slot.set(None);
                     manufacturer.update(String::clear);
                     model.update(String::clear);
-                    price.set(0);
+                    price.set(0.0);
                     is_submitting.set(false);
                     show.set(false);
                 },
@@ -167,11 +288,11 @@ fn AddComponentModal(
                 </GridItem>
 
                 <GridItem>
-                    <label for="price">"Price (R$)"</label>
+                    <label for="price">{format!("Price ({DISPLAY_CURRENCY})")}</label>
                 </GridItem>
 
                 <GridItem>
-                    <InputNumber attr:id="price" value=price step=100 />
+                    <InputNumber attr:id="price" value=price step=100.0 />
                 </GridItem>
             </Grid>
 
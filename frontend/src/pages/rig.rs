@@ -1,15 +1,17 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 
+use api_types::{component::Component, money::Money, rig::{CreateRigData, RigComponentData}, slot::Slot};
 use leptos::*;
-use leptos_meta::Style;
-use strum::VariantArray;
+use leptos_meta::{Style, Title};
+use leptos_router::use_params_map;
 use thaw::*;
+use uuid::Uuid;
 
+use crate::app::NotFound;
 use crate::pages::component::ComponentsTable;
-use crate::services::component::fetch_components;
-use crate::services::rig::{create_rig, fetch_rigs};
-use crate::types::rig::CreateRigData;
-use crate::types::{component::{ComponentData, Slot}, rig::Rig};
+use crate::services::component::fetch_slot_components;
+use crate::services::rig::{create_rig, fetch_rigs, fetch_shared_rig};
+use crate::types::{money::DISPLAY_CURRENCY, rig::Rig, slot::SlotsResource, user::CurrentUserResource};
 
 #[component]
 pub fn RigsPage() -> impl IntoView {
@@ -37,7 +39,7 @@ pub fn RigsPage() -> impl IntoView {
 
     view! {
         <RigsGrid rigs set_selected_rig set_add_rig=show_add_rig.write_only() />
-        <RigDetailModal rig=selected_rig />
+        <RigDetailModal rig=selected_rig rigs />
         <AddRig show=show_add_rig write_rig />
     }
 }
@@ -48,20 +50,26 @@ fn RigsGrid(
     #[prop(into)] set_selected_rig: WriteSignal<Option<Rig>>,
     #[prop(into)] set_add_rig: WriteSignal<bool>,
 ) -> impl IntoView {
+    // Rigs are created as the logged in user's
+    let current_user = expect_context::<CurrentUserResource>();
+    let can_add = Signal::derive(move || current_user.get().flatten().is_some());
+
     view! {
         <Grid cols=3 x_gap=8 y_gap=8>
             <For
                 each=rigs
-                key=|rig| rig.id.clone()
+                key=|rig| rig.id
                 let:rig
             >
                 <GridItem>
                     <RigItem rig set_selected_rig />
                 </GridItem>
             </For>
-            <GridItem>
-                <AddRigPlaceholder set_clicked=set_add_rig />
-            </GridItem>
+            <Show when=can_add>
+                <GridItem>
+                    <AddRigPlaceholder set_clicked=set_add_rig />
+                </GridItem>
+            </Show>
         </Grid>
     }
 }
@@ -89,7 +97,7 @@ fn RigItem(
                 {rig.name}
             </div>
             <div class="rig-desc">
-                R$ {rig.total_price},00
+                {rig.total_price.to_string()}
             </div>
         </div>
         <Style>
@@ -143,6 +151,8 @@ pub fn AddRigPlaceholder(
 #[component]
 pub fn RigDetailModal(
     #[prop(into)] rig: Signal<Option<Rig>>,
+    /// Every loaded rig, to name the one the rig was cloned from
+    #[prop(into)] rigs: Signal<Vec<Rig>>,
 ) -> impl IntoView
 {
     view! {
@@ -150,9 +160,17 @@ pub fn RigDetailModal(
             None => ().into_view(),
             Some(rig) => {
                 let name = rig.name.clone();
+                let parent_name = rig.parent_rig_id.as_ref().and_then(|parent_id| {
+                    rigs.with(|rigs| rigs.iter().find(|r| &r.id == parent_id).map(|r| r.name.clone()))
+                });
                 view! {
                     <Modal title={name} show=true z_index=1999>
-                        <ComponentsTable components=rig.components loaded=true />
+                        {parent_name.map(|parent_name| view! { <p>"Based on " {parent_name}</p> })}
+                        <ComponentsTable
+                            components=rig.components
+                            quantities=rig.quantities
+                            loaded=true
+                        />
                     </Modal>
                 }
             }
@@ -160,6 +178,127 @@ pub fn RigDetailModal(
     }
 }
 
+/// Read-only view of a rig opened through its public link
+#[component]
+pub fn SharedRigPage() -> impl IntoView {
+    let params = use_params_map();
+    let slug = move || params.with(|params| params.get("slug").cloned().unwrap_or_default());
+
+    // Blocking so the rig is part of the first response instead of being
+    // streamed in afterwards
+    let shared_rig = create_blocking_resource(slug, fetch_shared_rig);
+
+    view! {
+        <Suspense fallback=|| view! { <Spinner /> }>
+            {move || shared_rig.get().map(|result| match result {
+                Ok(Some(rig)) => view! {
+                    <Title text=rig.name.clone() />
+                    <h1>{rig.name}</h1>
+                    <ComponentsTable
+                        components=rig.components
+                        quantities=rig.quantities
+                        loaded=true
+                    />
+                    <h2>"Total: " {rig.total_price.to_string()}</h2>
+                }.into_view(),
+                Ok(None) => view! { <NotFound /> }.into_view(),
+                Err(err) => {
+                    logging::error!("Fetch Shared Rig Error: {err}");
+                    view! { <p>"Couldn't load this rig."</p> }.into_view()
+                },
+            })}
+        </Suspense>
+    }
+}
+
+/// The components picked for a slot: a radio selection for slots that hold a
+/// single component, a checkbox selection with a quantity per component
+/// otherwise.
+///
+/// Thaw keys radio and checkbox items by string, so selected ids are kept as
+/// strings and parsed back in [`Selection::items`].
+#[derive(Clone, Copy)]
+enum Selection {
+    One(RwSignal<Option<String>>),
+    Many {
+        selected: RwSignal<HashSet<String>>,
+        quantities: StoredValue<HashMap<Uuid, RwSignal<i32>>>,
+    },
+}
+
+impl Selection {
+    fn for_slot(slot: &Slot) -> Self {
+        if slot.allows_many() {
+            Self::Many {
+                selected: create_rw_signal(HashSet::new()),
+                quantities: store_value(HashMap::new()),
+            }
+        } else {
+            Self::One(create_rw_signal(None))
+        }
+    }
+
+    /// Starts the quantities of `components` at 1, as they're loaded
+    fn add_components(&self, components: &[Component]) {
+        if let Self::Many { quantities, .. } = self {
+            quantities.update_value(|quantities| {
+                for component in components {
+                    quantities.entry(component.id).or_insert_with(|| create_rw_signal(1));
+                }
+            });
+        }
+    }
+
+    /// The selected component ids with their quantities
+    fn items(&self) -> Vec<(Uuid, i32)> {
+        match self {
+            Self::One(selected) => selected()
+                .into_iter()
+                .filter_map(|id| Uuid::parse_str(&id).ok())
+                .map(|id| (id, 1))
+                .collect(),
+            Self::Many { selected, quantities } => selected()
+                .into_iter()
+                .filter_map(|id| Uuid::parse_str(&id).ok())
+                .map(|id| {
+                    let quantity = quantities.with_value(|quantities| {
+                        quantities.get(&id).map_or(1, |quantity| quantity())
+                    });
+                    (id, quantity)
+                })
+                .collect(),
+        }
+    }
+
+    fn count(&self) -> i32 {
+        self.items().iter().map(|(_, quantity)| quantity).sum()
+    }
+
+    fn clear(&self) {
+        match self {
+            Self::One(selected) => selected.set(None),
+            Self::Many { selected, quantities } => {
+                selected.update(HashSet::clear);
+                quantities.with_value(|quantities| {
+                    quantities.values().for_each(|quantity| quantity.set(1))
+                });
+            }
+        }
+    }
+}
+
+/// A slot's tab in the rig builder: the slot's components loaded so far, a
+/// page at a time, and the ones picked among them
+#[derive(Clone)]
+struct SlotOptions {
+    slot: Slot,
+    components: Vec<Component>,
+    selection: Selection,
+    /// Next page of the slot's components, `None` once they're all loaded or
+    /// while one is loading
+    next_page: Option<i64>,
+}
+
 #[component]
 pub fn AddRig(
     #[prop(into)] show: RwSignal<bool>,
@@ -167,57 +306,98 @@ pub fn AddRig(
 ) -> impl IntoView
 {
     let name = create_rw_signal(String::new());
-    let total_price = create_rw_signal(0);
+    let total_price = create_rw_signal(Money::zero(DISPLAY_CURRENCY));
 
     let name_invalid = Signal::derive(move || name().is_empty());
 
-    let current_tab = create_rw_signal(Slot::Cpu.to_string());
+    let current_tab = create_rw_signal(String::new());
+
+    let slots = expect_context::<SlotsResource>();
 
     let (components, set_components) = create_signal(
-        Slot::VARIANTS
-            .iter()
-            .map(|slot| (*slot, (Vec::<ComponentData>::new(), create_rw_signal(Option::<String>::None))))
-            .collect::<BTreeMap<_, (_, _)>>()
+        BTreeMap::<(i32, String), SlotOptions>::new()
     );
 
-    let components_loader = Resource::once(|| async {
-        fetch_components().await
-    });
+    let error = create_rw_signal(Option::<String>::None);
 
-    create_effect(move |_| {
-        components_loader.and_then(|comps| {
-            comps
-                .iter()
-                .cloned()
-                .for_each(|component| {
+    // Quantities are created as pages load, outside of any reactive scope
+    let owner = Owner::current().expect("AddRig is rendered inside a reactive owner");
+
+    // Appends the next page of the slot's components to its tab
+    let load_more = move |key: (i32, String)| {
+        let mut next_page = None;
+        set_components.update(|components| {
+            next_page = components.get_mut(&key).and_then(|options| options.next_page.take());
+        });
+        let Some(page) = next_page else {
+            return;
+        };
+
+        spawn_local(async move {
+            match fetch_slot_components(key.1.clone(), page).await {
+                Ok(loaded) => with_owner(owner, || set_components.update(|components| {
+                    if let Some(options) = components.get_mut(&key) {
+                        options.selection.add_components(&loaded.items);
+                        options.components.extend(loaded.items);
+                        options.next_page = (loaded.page < loaded.total_pages).then_some(loaded.page + 1);
+                    }
+                })),
+                Err(err) => {
+                    logging::error!("Fetch Slot Components Error: {err}");
+                    error.set(Some(err.to_string()));
                     set_components.update(|components| {
-                        components
-                            .get_mut(&component.slot)
-                            .unwrap()
-                            .0
-                            .push(component);
-                    })
-                })
+                        if let Some(options) = components.get_mut(&key) {
+                            options.next_page = Some(page);
+                        }
+                    });
+                },
+            }
         });
+    };
+
+    create_effect(move |_| {
+        let Some(slots) = slots.get() else {
+            return;
+        };
+
+        if let Some(first_slot) = slots.first() {
+            current_tab.set(first_slot.key.clone());
+        }
+
+        let options: BTreeMap<(i32, String), SlotOptions> = slots
+            .into_iter()
+            .map(|slot| {
+                let selection = Selection::for_slot(&slot);
+                let key = (slot.position, slot.key.clone());
+
+                (key, SlotOptions { slot, components: Vec::new(), selection, next_page: Some(1) })
+            })
+            .collect();
+        let keys: Vec<(i32, String)> = options.keys().cloned().collect();
+
+        set_components.set(options);
+        keys.into_iter().for_each(load_more);
     });
 
+    // Components are fetched in the display currency, so amounts add up
     create_effect(move |_| {
-        total_price.set(
-            components()
-                .values()
-                .map(|(slot_components, selected_component)| {
-                    if let Some(selected_component_id) = selected_component() {
-                        slot_components
+        let amount = components()
+            .values()
+            .flat_map(|options| {
+                options.selection
+                    .items()
+                    .into_iter()
+                    .filter_map(|(selected_component_id, quantity)| {
+                        options.components
                             .iter()
                             .find(|component| component.id == selected_component_id)
-                            .map(|component| component.price)
-                            .unwrap_or_default()
-                    } else {
-                        0
-                    }
-                })
-                .sum()
-        );
+                            .map(|component| component.price.saturating_times(quantity).amount)
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .fold(0i64, i64::saturating_add);
+
+        total_price.set(Money::new(amount, DISPLAY_CURRENCY));
     });
 
     let is_submitting = create_rw_signal(false);
@@ -227,7 +407,7 @@ pub fn AddRig(
         || name_invalid()
         || components()
             .values()
-            .any(|(_, selected_component)| selected_component().is_none())
+            .any(|options| !options.slot.accepts(options.selection.count()))
     );
 
     let on_click = move |_| {
@@ -235,11 +415,14 @@ pub fn AddRig(
             name: name(),
             components: components()
                 .values()
-                .map(|(_, selected_component_id)| selected_component_id().unwrap())
-                .collect()
+                .flat_map(|options| options.selection.items())
+                .map(|(component_id, quantity)| RigComponentData { component_id, quantity })
+                .collect(),
+            allow_incompatible: false,
         };
 
         is_submitting.set(true);
+        error.set(None);
 
         spawn_local(async move {
             let req = create_rig(rig).await;
@@ -247,15 +430,22 @@ pub fn AddRig(
                 Ok(rig) => {
                     write_rig.set(Some(rig));
                     name.update(String::clear);
-                    total_price.set(0);
+                    total_price.set(Money::zero(DISPLAY_CURRENCY));
                     is_submitting.set(false);
                     components()
                         .values()
-                        .for_each(|(_, selected_component_id)| selected_component_id.set(None));
+                        .for_each(|options| options.selection.clear());
 
                     show.set(false);
                 },
-                Err(err) => logging::error!("Create Rig Error: {err}"),
+                Err(err) => {
+                    logging::error!("Create Rig Error: {err}");
+                    error.set(Some(match err {
+                        ServerFnError::ServerError(message) => message,
+                        err => err.to_string(),
+                    }));
+                    is_submitting.set(false);
+                },
             }
         });
     };
@@ -268,7 +458,7 @@ pub fn AddRig(
                 </GridItem>
                 <GridItem>
                     <div class="total">
-                        Total: R$ {total_price}
+                        "Total: " {move || total_price().to_string()}
                     </div>
                     <Style>
                     ".total {
@@ -299,22 +489,67 @@ pub fn AddRig(
                     </Button>
                 </GridItem>
             </Grid>
+            {move || error().map(|error| view! {
+                <div class="error">{error}</div>
+                <Style>
+                ".error {
+                    color: #d03050;
+                    white-space: pre-line;
+                    padding: 8px 0;
+                }"
+                </Style>
+            })}
             <Divider />
             <Tabs value=current_tab>
-                {move || components().into_iter().map(|(slot, (slot_components, selected_component))| view! {
-                    <Tab key={slot.to_string()}>
+                {move || components().into_iter().map(|(key, SlotOptions { slot, components: slot_components, selection, next_page })| view! {
+                    <Tab key={slot.key.clone()}>
                         <TabLabel slot>
                             {slot}
                         </TabLabel>
-                        <RadioGroup value=selected_component>
-                            {slot_components.into_iter().map(|slot_component| {
-                                view! {
-                                    <RadioItem key={slot_component.id}>
-                                        {slot_component.manufacturer} | {slot_component.model} - R$ {slot_component.price},00
-                                    </RadioItem>
-                                }
-                            }).collect::<Vec<_>>()}
-                        </RadioGroup>
+                        {match selection {
+                            Selection::One(selected_component) => view! {
+                                <RadioGroup value=selected_component>
+                                    {slot_components.into_iter().map(|slot_component| {
+                                        view! {
+                                            <RadioItem key={slot_component.id.to_string()}>
+                                                {slot_component.manufacturer} | {slot_component.model} - {slot_component.price.to_string()}
+                                            </RadioItem>
+                                        }
+                                    }).collect::<Vec<_>>()}
+                                </RadioGroup>
+                            }.into_view(),
+                            Selection::Many { selected, quantities } => view! {
+                                <CheckboxGroup value=selected>
+                                    {slot_components.into_iter().map(|slot_component| {
+                                        let label = format!(
+                                            "{} | {} - {}",
+                                            slot_component.manufacturer,
+                                            slot_component.model,
+                                            slot_component.price,
+                                        );
+                                        let quantity = quantities.with_value(|quantities| {
+                                            quantities[&slot_component.id]
+                                        });
+                                        view! {
+                                            <div class="quantity-item">
+                                                <CheckboxItem key={slot_component.id.to_string()} label />
+                                                <InputNumber value=quantity step=1 />
+                                            </div>
+                                        }
+                                    }).collect::<Vec<_>>()}
+                                </CheckboxGroup>
+                                <Style>
+                                ".quantity-item {
+                                    display: flex;
+                                    align-items: center;
+                                    gap: 8px;
+                                }"
+                                </Style>
+                            }.into_view(),
+                        }}
+                        {next_page.map(|_| view! {
+                            <Button on_click=move |_| load_more(key.clone())>"Load more"</Button>
+                        })}
                     </Tab>
                 }).collect::<Vec<_>>()}
             </Tabs> 
@@ -0,0 +1,118 @@
+use leptos::*;
+use leptos_meta::Style;
+use leptos_router::{use_navigate, A};
+use thaw::*;
+
+use crate::{services::user::{log_in, log_out}, types::user::CurrentUserResource};
+
+#[component]
+pub fn LoginPage() -> impl IntoView {
+    let current_user = expect_context::<CurrentUserResource>();
+
+    let email = create_rw_signal(String::new());
+    let password = create_rw_signal(String::new());
+    let error = create_rw_signal(Option::<String>::None);
+
+    let is_submitting = create_rw_signal(false);
+    let submit_is_disabled = Signal::derive(move || is_submitting() || email().is_empty() || password().is_empty());
+
+    let navigate = use_navigate();
+    let on_click = move |_| {
+        is_submitting.set(true);
+        error.set(None);
+
+        let navigate = navigate.clone();
+        spawn_local(async move {
+            match log_in(email(), password()).await {
+                Ok(user) => {
+                    current_user.set(Some(user));
+                    password.update(String::clear);
+                    navigate("/rigs", Default::default());
+                },
+                Err(err) => {
+                    logging::error!("Log In Error: {err}");
+                    error.set(Some("Wrong email or password".to_owned()));
+                },
+            }
+            is_submitting.set(false);
+        });
+    };
+
+    view! {
+        <Grid cols=2>
+            <GridItem>
+                <label for="email">"Email"</label>
+            </GridItem>
+
+            <GridItem>
+                <Input attr:id="email" value=email />
+            </GridItem>
+
+            <GridItem>
+                <label for="password">"Password"</label>
+            </GridItem>
+
+            <GridItem>
+                <Input attr:id="password" value=password variant=InputVariant::Password />
+            </GridItem>
+        </Grid>
+
+        {move || error().map(|error| view! { <p class="login-error">{error}</p> })}
+        <Style>
+        ".login-error {
+            color: #d03050;
+        }"
+        </Style>
+
+        <Button
+            block=true
+            disabled=submit_is_disabled
+            on_click
+        >
+            {move || if is_submitting() {
+                view! {
+                    <Spinner size=SpinnerSize::Tiny/>
+                }
+            } else {
+                "Log in".into_view()
+            }}
+        </Button>
+    }
+}
+
+/// Who is logged in, with a way out, or a link to log in
+#[component]
+pub fn SessionBar() -> impl IntoView {
+    let current_user = expect_context::<CurrentUserResource>();
+
+    let on_log_out = move |_| {
+        spawn_local(async move {
+            match log_out().await {
+                Ok(()) => current_user.set(None),
+                Err(err) => logging::error!("Log Out Error: {err}"),
+            }
+        });
+    };
+
+    view! {
+        <div class="session-bar">
+            {move || match current_user.get().flatten() {
+                Some(user) => view! {
+                    <span>{user.email}</span>
+                    <Button on_click=on_log_out>"Log out"</Button>
+                }.into_view(),
+                None => view! {
+                    <A href="/login">"Log in"</A>
+                }.into_view(),
+            }}
+        </div>
+        <Style>
+        ".session-bar {
+            display: flex;
+            gap: 8px;
+            align-items: center;
+            justify-content: flex-end;
+        }"
+        </Style>
+    }
+}
@@ -1,32 +1,41 @@
-use std::time::Duration;
-
+use api_types::{component::{Component, ComponentQuery, CreateComponentData}, page::{Page, MAX_PER_PAGE}};
 use leptos::*;
 
-use crate::types::component::{ComponentData, CreateComponentData};
+use crate::types::money::DISPLAY_CURRENCY;
+
+/// One page of the catalog, as the component table shows it
+#[server]
+pub async fn fetch_components(page: i64) -> Result<Page<Component>, ServerFnError> {
+    let query = ComponentQuery {
+        currency: Some(DISPLAY_CURRENCY.to_owned()),
+        page: Some(page),
+        ..ComponentQuery::default()
+    };
+
+    Ok(super::backend().await.list_components(&query).await?)
+}
 
+/// One page of a slot's components, for the rig builder to offer as options
 #[server]
-pub async fn fetch_components() -> Result<Vec<ComponentData>, ServerFnError> {
-    actix_web::rt::time::sleep(Duration::from_secs(3)).await;
+pub async fn fetch_slot_components(slot: String, page: i64) -> Result<Page<Component>, ServerFnError> {
+    let query = ComponentQuery {
+        slot: Some(slot),
+        currency: Some(DISPLAY_CURRENCY.to_owned()),
+        page: Some(page),
+        per_page: Some(MAX_PER_PAGE),
+        ..ComponentQuery::default()
+    };
 
-    Ok(
-        reqwest::get("http://localhost:8000/components")
-        .await?
-        .json::<Vec<ComponentData>>()
-        .await?
-    )
+    Ok(super::backend().await.list_components(&query).await?)
 }
 
 #[server]
-pub async fn create_component(create_component_data: CreateComponentData) -> Result<ComponentData, ServerFnError> {
-    actix_web::rt::time::sleep(Duration::from_secs(3)).await;
+pub async fn search_components(query: String) -> Result<Vec<Component>, ServerFnError> {
+    Ok(super::backend().await.search_components(&query, None, Some(DISPLAY_CURRENCY)).await?)
+}
 
-    let client = reqwest::Client::new();
-    Ok(
-        client.post("http://localhost:8000/components")
-        .json(&create_component_data)
-        .send()
-        .await?
-        .json::<ComponentData>()
-        .await?
-    )
-}
\ No newline at end of file
+/// Adding to the catalog takes an admin's session
+#[server]
+pub async fn create_component(create_component_data: CreateComponentData) -> Result<Component, ServerFnError> {
+    Ok(super::authenticated_backend().await?.create_component(&create_component_data).await?)
+}
@@ -1,2 +1,36 @@
 pub mod component;
-pub mod rig;
\ No newline at end of file
+pub mod rig;
+pub mod slot;
+pub mod user;
+
+/// Client for the configured backend, handed out once the configured
+/// artificial latency has passed
+#[cfg(feature = "ssr")]
+pub async fn backend() -> api_client::RigApiClient {
+    let config = leptos::expect_context::<crate::config::FrontendConfig>();
+
+    if let Some(latency) = config.artificial_latency {
+        actix_web::rt::time::sleep(latency).await;
+    }
+
+    config.api_client()
+}
+
+/// [`backend`] acting as the logged in user, `None` when nobody is
+#[cfg(feature = "ssr")]
+pub async fn session_backend() -> Result<Option<api_client::RigApiClient>, leptos::ServerFnError> {
+    let request: actix_web::HttpRequest = leptos_actix::extract().await?;
+
+    match request.cookie(user::SESSION_COOKIE) {
+        Some(cookie) => Ok(Some(backend().await.with_token(cookie.value()))),
+        None => Ok(None),
+    }
+}
+
+/// [`backend`] acting as the logged in user, failing when nobody is
+#[cfg(feature = "ssr")]
+pub async fn authenticated_backend() -> Result<api_client::RigApiClient, leptos::ServerFnError> {
+    session_backend()
+        .await?
+        .ok_or_else(|| leptos::ServerFnError::new("log in first"))
+}
@@ -1,14 +1,12 @@
-use std::time::Duration;
-
+use api_types::rig::CreateRigData;
 use leptos::*;
 
-use crate::types::{component::{ComponentData, CreateComponentData}, rig::{CreateRigData, Rig, RigData}};
+use crate::types::{money::DISPLAY_CURRENCY, rig::Rig};
 
 #[server]
 pub async fn fetch_rigs() -> Result<Vec<Rig>, ServerFnError> {
-    let rigs_data = reqwest::get("http://localhost:8000/rigs")
-        .await?
-        .json::<Vec<RigData>>()
+    let rigs_data = super::backend().await
+        .list_rigs(Some(DISPLAY_CURRENCY), false)
         .await?;
 
     let rigs: Vec<Rig> = rigs_data
@@ -19,15 +17,22 @@ pub async fn fetch_rigs() -> Result<Vec<Rig>, ServerFnError> {
     Ok(rigs)
 }
 
+/// The rig behind a public link, `None` if the link doesn't exist or was
+/// revoked
 #[server]
-pub async fn create_rig(create_rig_data: CreateRigData) -> Result<Rig, ServerFnError> {
-    let client = reqwest::Client::new();
+pub async fn fetch_shared_rig(slug: String) -> Result<Option<Rig>, ServerFnError> {
+    match super::backend().await.get_shared_rig(&slug, Some(DISPLAY_CURRENCY)).await {
+        Ok(rig) => Ok(Some(rig.into())),
+        Err(err) if err.is_not_found() => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
 
-    Ok(client.post("http://localhost:8000/rigs")
-        .json(&create_rig_data)
-        .send()
-        .await?
-        .json::<RigData>()
+/// Created as the logged in user's rig
+#[server]
+pub async fn create_rig(create_rig_data: CreateRigData) -> Result<Rig, ServerFnError> {
+    Ok(super::authenticated_backend().await?
+        .create_rig(&create_rig_data, Some(DISPLAY_CURRENCY))
         .await?
         .into())
 }
@@ -0,0 +1,7 @@
+use api_types::slot::Slot;
+use leptos::*;
+
+#[server]
+pub async fn fetch_slots() -> Result<Vec<Slot>, ServerFnError> {
+    Ok(super::backend().await.list_slots().await?)
+}
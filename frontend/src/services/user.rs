@@ -0,0 +1,72 @@
+use api_types::user::User;
+use leptos::*;
+
+/// Cookie the backend session token is kept in, out of reach of scripts
+#[cfg(feature = "ssr")]
+pub const SESSION_COOKIE: &str = "session";
+
+/// Logs in to the backend, keeping its session in a cookie so the server
+/// functions called afterwards act as the user
+#[server]
+pub async fn log_in(email: String, password: String) -> Result<User, ServerFnError> {
+    use actix_web::cookie::{Cookie, SameSite};
+    use api_types::user::CredentialsData;
+
+    let session = super::backend().await
+        .login(&CredentialsData { email, password })
+        .await?;
+
+    let cookie = Cookie::build(SESSION_COOKIE, session.token)
+        .path("/")
+        .http_only(true)
+        .same_site(SameSite::Lax)
+        .expires(session.expires_at)
+        .finish();
+    set_cookie(cookie)?;
+
+    Ok(session.user)
+}
+
+/// Ends the session on the backend and forgets its cookie
+#[server]
+pub async fn log_out() -> Result<(), ServerFnError> {
+    use actix_web::cookie::{time::Duration, Cookie};
+
+    if let Some(client) = super::session_backend().await? {
+        match client.logout().await {
+            // Already gone on the backend, there's only the cookie left
+            Err(err) if !err.is_unauthorized() => return Err(err.into()),
+            _ => {},
+        }
+    }
+
+    let cookie = Cookie::build(SESSION_COOKIE, "")
+        .path("/")
+        .max_age(Duration::ZERO)
+        .finish();
+    set_cookie(cookie)
+}
+
+/// The logged in user, `None` without a session or once it expired
+#[server]
+pub async fn fetch_current_user() -> Result<Option<User>, ServerFnError> {
+    let Some(client) = super::session_backend().await? else {
+        return Ok(None);
+    };
+
+    match client.get_current_user().await {
+        Ok(user) => Ok(Some(user)),
+        Err(err) if err.is_unauthorized() => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[cfg(feature = "ssr")]
+fn set_cookie(cookie: actix_web::cookie::Cookie<'_>) -> Result<(), ServerFnError> {
+    use actix_web::http::header::{HeaderValue, SET_COOKIE};
+
+    let value = HeaderValue::from_str(&cookie.to_string())?;
+    expect_context::<leptos_actix::ResponseOptions>().append_header(SET_COOKIE, value);
+
+    Ok(())
+}
@@ -1,2 +1,4 @@
-pub mod component;
-pub mod rig;
\ No newline at end of file
+pub mod money;
+pub mod rig;
+pub mod slot;
+pub mod user;
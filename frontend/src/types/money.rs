@@ -0,0 +1,2 @@
+/// Currency prices are shown in; the backend converts everything into it
+pub const DISPLAY_CURRENCY: &str = "BRL";
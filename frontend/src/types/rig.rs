@@ -1,41 +1,40 @@
-use serde::{Deserialize, Serialize};
-
-use super::component::ComponentData;
+use std::collections::HashMap;
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
-pub struct RigData {
-    pub id: String,
-    pub name: String,
-    pub components: Vec<ComponentData>,
-}
+use api_types::{component::Component, money::Money, rig::RigWithComponents};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Rig {
-    pub id: String,
+    pub id: Uuid,
     pub name: String,
-    pub components: Vec<ComponentData>,
-    pub total_price: i32,
+    pub components: Vec<Component>,
+    /// Units of each component, by component id
+    pub quantities: HashMap<Uuid, i32>,
+    pub total_price: Money,
+    /// Set while the rig is readable by anyone at `/r/<share_slug>`
+    pub share_slug: Option<String>,
+    /// Rig this one was cloned from
+    pub parent_rig_id: Option<Uuid>,
 }
 
-impl From<RigData> for Rig {
-    fn from(value: RigData) -> Self {
-        let RigData { id, name, components } = value;
-        let total_price = components
+impl From<RigWithComponents> for Rig {
+    fn from(value: RigWithComponents) -> Self {
+        let RigWithComponents { id, name, components, total_price, share_slug, parent_rig_id, .. } = value;
+
+        let quantities = components
             .iter()
-            .map(|c| c.price)
-            .sum();
+            .map(|c| (c.component.id, c.quantity))
+            .collect();
 
         Self {
             id,
             name,
-            components,
+            components: components.into_iter().map(|c| c.component).collect(),
+            quantities,
             total_price,
+            share_slug,
+            parent_rig_id,
         }
     }
 }
-
-#[derive(Serialize, Deserialize, Clone, Debug)]
-pub struct CreateRigData {
-    pub name: String,
-    pub components: Vec<String>,
-}
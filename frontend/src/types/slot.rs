@@ -0,0 +1,5 @@
+use api_types::slot::Slot;
+use leptos::*;
+
+/// Slots are loaded once by [`App`](crate::app::App) and shared through context.
+pub type SlotsResource = Resource<(), Vec<Slot>>;
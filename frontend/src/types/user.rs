@@ -0,0 +1,7 @@
+use api_types::user::User;
+use leptos::*;
+
+/// The logged in user, `None` when nobody is. Loaded once by
+/// [`App`](crate::app::App) and shared through context; logging in and out
+/// set it.
+pub type CurrentUserResource = Resource<(), Option<User>>;